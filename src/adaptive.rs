@@ -0,0 +1,349 @@
+// E:\rust_dev\kids_story_trainer\src\adaptive.rs
+//
+// Adaptive difficulty: looks at the child's recent finished stories and
+// picks the grade level, length and question difficulty for the next one.
+// Pure logic over `SessionRecord`s so it can be exercised without Yew.
+use serde::{Deserialize, Serialize};
+
+use crate::storage::SessionRecord;
+
+// Grades run from 0 (kindergarten) to 8.
+pub const MAX_GRADE: u8 = 8;
+pub const MIN_PARAGRAPHS: u8 = 1;
+pub const MAX_PARAGRAPHS: u8 = 6;
+
+// Only the last few stories at the current level count, so one bad day
+// doesn't undo a week of progress.
+const RECENT_WINDOW: usize = 3;
+const MIN_SESSIONS_TO_ADJUST: usize = 2;
+
+const STEP_UP_SCORE: f32 = 90.0;
+const STEP_UP_FIRST_TRY: f32 = 80.0;
+const STEP_DOWN_SCORE: f32 = 60.0;
+const STEP_DOWN_ATTEMPTS: f32 = 2.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionDifficulty {
+    Easy,
+    #[default]
+    Standard,
+    Challenge,
+}
+
+impl QuestionDifficulty {
+    pub fn key(self) -> &'static str {
+        match self {
+            QuestionDifficulty::Easy => "easy",
+            QuestionDifficulty::Standard => "standard",
+            QuestionDifficulty::Challenge => "challenge",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+pub struct DifficultyLevel {
+    pub grade_level: u8,
+    pub num_paragraphs: u8,
+    pub question_difficulty: QuestionDifficulty,
+}
+
+impl Default for DifficultyLevel {
+    fn default() -> Self {
+        DifficultyLevel {
+            grade_level: 5,
+            num_paragraphs: 3,
+            question_difficulty: QuestionDifficulty::Standard,
+        }
+    }
+}
+
+impl DifficultyLevel {
+    fn harder(self) -> DifficultyLevel {
+        if self.grade_level < MAX_GRADE {
+            DifficultyLevel {
+                grade_level: self.grade_level + 1,
+                question_difficulty: QuestionDifficulty::Standard,
+                ..self
+            }
+        } else {
+            DifficultyLevel {
+                num_paragraphs: (self.num_paragraphs + 1).min(MAX_PARAGRAPHS),
+                question_difficulty: QuestionDifficulty::Challenge,
+                ..self
+            }
+        }
+    }
+
+    fn easier(self) -> DifficultyLevel {
+        if self.question_difficulty > QuestionDifficulty::Easy {
+            // First make the questions gentler and the passage shorter...
+            DifficultyLevel {
+                num_paragraphs: self.num_paragraphs.saturating_sub(1).max(MIN_PARAGRAPHS),
+                question_difficulty: QuestionDifficulty::Easy,
+                ..self
+            }
+        } else if self.grade_level == 0 {
+            // Already as easy as it gets
+            self
+        } else {
+            // ...then drop a grade level.
+            DifficultyLevel {
+                grade_level: self.grade_level.saturating_sub(1),
+                question_difficulty: QuestionDifficulty::Standard,
+                ..self
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Recommendation {
+    pub level: DifficultyLevel,
+    pub reason: String,
+}
+
+// Recommends the level for the next story. The current level is the one the
+// most recent story was read at (or `start` if there is none yet).
+pub fn recommend(history: &[SessionRecord], start: DifficultyLevel) -> Recommendation {
+    let current = history
+        .iter()
+        .rev()
+        .find_map(|rec| rec.level)
+        .unwrap_or(start);
+
+    let recent: Vec<&SessionRecord> = history
+        .iter()
+        .rev()
+        .take_while(|rec| rec.level == Some(current))
+        .take(RECENT_WINDOW)
+        .collect();
+
+    if recent.len() < MIN_SESSIONS_TO_ADJUST {
+        return Recommendation {
+            level: current,
+            reason: format!(
+                "Staying here until {} stories are finished at this level.",
+                MIN_SESSIONS_TO_ADJUST
+            ),
+        };
+    }
+
+    let stats = RecentStats::from_sessions(&recent);
+
+    if stats.avg_score >= STEP_UP_SCORE && stats.first_try_percent >= STEP_UP_FIRST_TRY {
+        let next = current.harder();
+        let reason = format!(
+            "Last {} stories averaged {:.0}% with {:.0}% right on the first try – time for a challenge!",
+            recent.len(),
+            stats.avg_score,
+            stats.first_try_percent
+        );
+        return Recommendation { level: next, reason };
+    }
+
+    if stats.avg_score < STEP_DOWN_SCORE || stats.avg_attempts >= STEP_DOWN_ATTEMPTS {
+        let next = current.easier();
+        let reason = if stats.avg_score < STEP_DOWN_SCORE {
+            format!(
+                "Last {} stories averaged {:.0}%, so the next one will be a little easier.",
+                recent.len(),
+                stats.avg_score
+            )
+        } else {
+            format!(
+                "Questions took about {:.1} tries each, so the next story will be a little easier.",
+                stats.avg_attempts
+            )
+        };
+        return Recommendation { level: next, reason };
+    }
+
+    Recommendation {
+        level: current,
+        reason: format!(
+            "Last {} stories averaged {:.0}% – this level is just right.",
+            recent.len(),
+            stats.avg_score
+        ),
+    }
+}
+
+struct RecentStats {
+    avg_score: f32,
+    first_try_percent: f32,
+    // Attempts per answered (not skipped) question.
+    avg_attempts: f32,
+}
+
+impl RecentStats {
+    fn from_sessions(sessions: &[&SessionRecord]) -> RecentStats {
+        let scores: Vec<f32> = sessions
+            .iter()
+            .map(|rec| rec.score_percent.unwrap_or(0) as f32)
+            .collect();
+        let avg_score = scores.iter().sum::<f32>() / scores.len().max(1) as f32;
+
+        let progress = sessions.iter().flat_map(|rec| rec.progress.iter());
+        let mut questions = 0;
+        let mut first_try = 0;
+        let mut answered = 0;
+        let mut attempts = 0;
+        for qp in progress {
+            questions += 1;
+            if qp.is_correct && qp.attempts == 1 {
+                first_try += 1;
+            }
+            if !qp.skipped {
+                answered += 1;
+                attempts += qp.attempts;
+            }
+        }
+
+        RecentStats {
+            avg_score,
+            first_try_percent: first_try as f32 * 100.0 / questions.max(1) as f32,
+            avg_attempts: attempts as f32 / answered.max(1) as f32,
+        }
+    }
+}
+
+// "K" for kindergarten, otherwise the number.
+pub fn grade_label(grade_level: u8) -> String {
+    if grade_level == 0 {
+        "K".into()
+    } else {
+        grade_level.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QuestionProgress;
+
+    fn level(
+        grade_level: u8,
+        num_paragraphs: u8,
+        question_difficulty: QuestionDifficulty,
+    ) -> DifficultyLevel {
+        DifficultyLevel {
+            grade_level,
+            num_paragraphs,
+            question_difficulty,
+        }
+    }
+
+    // A finished story at `level` with four questions each taking `attempts`.
+    fn session(level: DifficultyLevel, score: i32, attempts: u32) -> SessionRecord {
+        let qp = QuestionProgress {
+            attempts,
+            is_correct: true,
+            skipped: false,
+            credit: 1.0,
+            first_try_credit: if attempts == 1 { 1.0 } else { 0.0 },
+        };
+        SessionRecord {
+            story: Default::default(),
+            progress: vec![qp; 4],
+            score_percent: Some(score),
+            used_ai: false,
+            level: Some(level),
+            completed_at: 0.0,
+        }
+    }
+
+    #[test]
+    fn holds_until_enough_stories_at_the_level() {
+        let start = level(3, 3, QuestionDifficulty::Standard);
+        assert_eq!(recommend(&[], start).level, start);
+        assert_eq!(recommend(&[session(start, 100, 1)], start).level, start);
+    }
+
+    #[test]
+    fn raises_grade_after_strong_first_try_results() {
+        let start = level(3, 3, QuestionDifficulty::Easy);
+        let history = vec![session(start, 95, 1), session(start, 100, 1)];
+        assert_eq!(
+            recommend(&history, start).level,
+            level(4, 3, QuestionDifficulty::Standard)
+        );
+    }
+
+    #[test]
+    fn raises_length_and_questions_at_the_top_grade() {
+        let start = level(MAX_GRADE, 5, QuestionDifficulty::Standard);
+        let history = vec![session(start, 100, 1), session(start, 100, 1)];
+        assert_eq!(
+            recommend(&history, start).level,
+            level(MAX_GRADE, 6, QuestionDifficulty::Challenge)
+        );
+
+        let top = level(MAX_GRADE, MAX_PARAGRAPHS, QuestionDifficulty::Challenge);
+        let history = vec![session(top, 100, 1), session(top, 100, 1)];
+        assert_eq!(recommend(&history, top).level, top);
+    }
+
+    #[test]
+    fn lowers_questions_and_length_before_grade() {
+        let start = level(4, 3, QuestionDifficulty::Standard);
+        let history = vec![session(start, 40, 1), session(start, 50, 1)];
+        assert_eq!(
+            recommend(&history, start).level,
+            level(4, 2, QuestionDifficulty::Easy)
+        );
+
+        let easy = level(4, 2, QuestionDifficulty::Easy);
+        let history = vec![session(easy, 40, 1), session(easy, 50, 1)];
+        assert_eq!(
+            recommend(&history, easy).level,
+            level(3, 2, QuestionDifficulty::Standard)
+        );
+    }
+
+    #[test]
+    fn lowers_when_questions_take_many_tries() {
+        let start = level(4, 3, QuestionDifficulty::Standard);
+        let history = vec![session(start, 75, 3), session(start, 80, 2)];
+        assert_eq!(
+            recommend(&history, start).level,
+            level(4, 2, QuestionDifficulty::Easy)
+        );
+    }
+
+    #[test]
+    fn lowering_stops_at_kindergarten_and_one_paragraph() {
+        let start = level(0, MIN_PARAGRAPHS, QuestionDifficulty::Standard);
+        let history = vec![session(start, 10, 1), session(start, 20, 1)];
+        assert_eq!(
+            recommend(&history, start).level,
+            level(0, MIN_PARAGRAPHS, QuestionDifficulty::Easy)
+        );
+
+        let easy = level(0, MIN_PARAGRAPHS, QuestionDifficulty::Easy);
+        let history = vec![session(easy, 10, 1), session(easy, 20, 1)];
+        assert_eq!(
+            recommend(&history, easy).level,
+            level(0, MIN_PARAGRAPHS, QuestionDifficulty::Easy)
+        );
+    }
+
+    #[test]
+    fn holds_in_the_middle_band() {
+        let start = level(4, 3, QuestionDifficulty::Standard);
+        let history = vec![session(start, 75, 1), session(start, 80, 1)];
+        assert_eq!(recommend(&history, start).level, start);
+    }
+
+    #[test]
+    fn only_stories_at_the_current_level_count() {
+        let old = level(3, 3, QuestionDifficulty::Standard);
+        let current = level(4, 3, QuestionDifficulty::Standard);
+        let history = vec![
+            session(old, 100, 1),
+            session(old, 100, 1),
+            session(current, 100, 1),
+        ];
+        assert_eq!(recommend(&history, old).level, current);
+    }
+}
@@ -0,0 +1,214 @@
+// E:\rust_dev\kids_story_trainer\src\config.rs
+//
+// Runtime backend configuration, so a school can point the app at its own
+// Worker, chat model or local server without rebuilding. Loaded from `config.json` next to index.html;
+// `?backend=<profile>` picks a profile and `?endpoint=<url>` adds a one-off
+// "custom" profile. Without a config file the built-in profiles are used.
+use gloo_net::http::Request;
+use serde::{Deserialize, Serialize};
+
+// 🔗 Default Cloudflare Worker URL (used when there is no config.json)
+pub const DEFAULT_WORKER_URL: &str =
+    "https://kids-story-worker.mikegyver.workers.dev/api/story";
+
+const CONFIG_URL: &str = "config.json";
+pub const OFFLINE_PROFILE: &str = "offline";
+const CUSTOM_PROFILE: &str = "custom";
+
+fn default_timeout_ms() -> u32 {
+    20_000
+}
+
+fn default_max_retries() -> u32 {
+    2
+}
+
+fn default_retry_backoff_ms() -> u32 {
+    1_000
+}
+
+// Which wire format a profile speaks.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    // Our Cloudflare Worker (see README for the JSON contract).
+    #[default]
+    Worker,
+    // Any OpenAI-compatible `/v1/chat/completions` endpoint.
+    OpenAiChat,
+    // A local Ollama-style server (`/api/chat`).
+    Ollama,
+    // No network at all: the built-in story library.
+    Builtin,
+}
+
+// Extra header sent with every story request, e.g. an API gateway key.
+// Anything in config.json is readable by every visitor, so only use
+// credentials that are meant to be public (rate-limited, origin-locked).
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct AuthHeader {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct BackendProfile {
+    pub name: String,
+    #[serde(default)]
+    pub label: String,
+    // No endpoint means "no AI": always use the built-in stories.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u32,
+    // Extra tries after a 5xx or network error; the wait before each one
+    // doubles, starting at `retry_backoff_ms`.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u32,
+    #[serde(default)]
+    pub auth_header: Option<AuthHeader>,
+    #[serde(default)]
+    pub kind: ProviderKind,
+    // Ask the Worker to stream the story line by line (see stream.rs).
+    #[serde(default)]
+    pub stream: bool,
+    // Model name for chat-style providers (ignored by the Worker).
+    #[serde(default)]
+    pub model: Option<String>,
+    // Other profiles to try, in order, if this one fails. The built-in
+    // stories are always tried last.
+    #[serde(default)]
+    pub fallback_profiles: Vec<String>,
+}
+
+impl BackendProfile {
+    pub fn offline() -> BackendProfile {
+        BackendProfile {
+            name: OFFLINE_PROFILE.into(),
+            label: "No AI (built-in stories only)".into(),
+            endpoint: None,
+            timeout_ms: default_timeout_ms(),
+            max_retries: default_max_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            auth_header: None,
+            kind: ProviderKind::Builtin,
+            stream: false,
+            model: None,
+            fallback_profiles: Vec::new(),
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        if self.label.is_empty() {
+            &self.name
+        } else {
+            &self.label
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct AppConfig {
+    pub default_profile: String,
+    pub profiles: Vec<BackendProfile>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            default_profile: "cloudflare".into(),
+            profiles: vec![
+                BackendProfile {
+                    name: "cloudflare".into(),
+                    label: "Cloudflare Worker AI".into(),
+                    endpoint: Some(DEFAULT_WORKER_URL.into()),
+                    timeout_ms: default_timeout_ms(),
+                    max_retries: default_max_retries(),
+                    retry_backoff_ms: default_retry_backoff_ms(),
+                    auth_header: None,
+                    kind: ProviderKind::Worker,
+                    stream: false,
+                    model: None,
+                    fallback_profiles: Vec::new(),
+                },
+                BackendProfile::offline(),
+            ],
+        }
+    }
+}
+
+impl AppConfig {
+    // The named profile, else the default one, else offline.
+    pub fn profile(&self, name: &str) -> BackendProfile {
+        self.profiles
+            .iter()
+            .find(|p| p.name == name)
+            .or_else(|| self.profiles.iter().find(|p| p.name == self.default_profile))
+            .cloned()
+            .unwrap_or_else(BackendProfile::offline)
+    }
+
+    // Every config offers the offline profile, even if the file forgot it.
+    fn ensure_offline_profile(&mut self) {
+        if !self.profiles.iter().any(|p| p.name == OFFLINE_PROFILE) {
+            self.profiles.push(BackendProfile::offline());
+        }
+    }
+
+    fn apply_query_overrides(&mut self) {
+        let Some(search) = web_sys::window().and_then(|w| w.location().search().ok()) else {
+            return;
+        };
+        let Ok(params) = web_sys::UrlSearchParams::new_with_str(&search) else {
+            return;
+        };
+
+        if let Some(endpoint) = params.get("endpoint").filter(|e| !e.trim().is_empty()) {
+            self.profiles.retain(|p| p.name != CUSTOM_PROFILE);
+            self.profiles.push(BackendProfile {
+                name: CUSTOM_PROFILE.into(),
+                label: "Custom endpoint (from link)".into(),
+                endpoint: Some(endpoint.trim().to_string()),
+                timeout_ms: default_timeout_ms(),
+                max_retries: default_max_retries(),
+                retry_backoff_ms: default_retry_backoff_ms(),
+                auth_header: None,
+                kind: ProviderKind::Worker,
+                stream: false,
+                model: None,
+                fallback_profiles: Vec::new(),
+            });
+            self.default_profile = CUSTOM_PROFILE.into();
+        }
+        if let Some(name) = params.get("backend") {
+            if self.profiles.iter().any(|p| p.name == name) {
+                self.default_profile = name;
+            }
+        }
+    }
+}
+
+// Loads config.json (if the site has one) and applies query parameters.
+// Returns a warning for the error banner when the file exists but is broken.
+pub async fn load() -> (AppConfig, Option<String>) {
+    let mut warning = None;
+    let mut config = match Request::get(CONFIG_URL).send().await {
+        Ok(resp) if resp.ok() => match resp.json::<AppConfig>().await {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                warning = Some(format!(
+                    "config.json could not be read; using built-in settings. ({})",
+                    e
+                ));
+                AppConfig::default()
+            }
+        },
+        // No config file (404) or no network: built-in profiles, quietly.
+        _ => AppConfig::default(),
+    };
+    config.ensure_offline_profile();
+    config.apply_query_overrides();
+    (config, warning)
+}
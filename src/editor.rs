@@ -0,0 +1,1029 @@
+// E:\rust_dev\kids_story_trainer\src\editor.rs
+//
+// Teacher story editor. A grown-up writes the title, paragraphs and
+// questions by hand, sees what validation thinks of the story while typing,
+// tries it through the same question screen a student gets, and collects
+// finished stories into a pack that can be downloaded (JSON or TOML) or
+// saved straight into this browser's story library.
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+use crate::pack::{self, PackStory, StoryPack};
+use crate::quiz::use_quiz;
+use crate::validate::{IssueAction, ValidationReport};
+use crate::{split_sentences, AnswerMatching, Question, QuestionKind, Story};
+
+// Question types in the order the picker offers them.
+const KIND_KEYS: [&str; 6] = [
+    "multiple_choice",
+    "true_false",
+    "short_answer",
+    "evidence_select",
+    "sequence",
+    "vocabulary",
+];
+
+#[derive(Properties, PartialEq)]
+pub struct StoryEditorProps {
+    pub on_save_pack: Callback<StoryPack>,
+    pub on_close: Callback<MouseEvent>,
+    // Story to start from instead of a blank one (editing a loaded story).
+    #[prop_or_default]
+    pub story: Option<Story>,
+    // Set when editing the loaded story: called with the finished story and,
+    // if the lock checkbox was changed, whether students should now be
+    // locked to it (None leaves the lock alone). Replaces the pack tools.
+    #[prop_or_default]
+    pub on_use_story: Option<Callback<(PackStory, Option<bool>)>>,
+    #[prop_or_default]
+    pub locked: bool,
+}
+
+#[function_component(StoryEditor)]
+pub fn story_editor(props: &StoryEditorProps) -> Html {
+    let story_pack = use_state(|| pack::new_pack("My stories"));
+    let draft = {
+        let initial = props.story.clone();
+        use_state(move || initial.unwrap_or_else(blank_story))
+    };
+    let draft_tags = use_state(String::new);
+    let lock_story = {
+        let locked = props.locked;
+        use_state(move || locked)
+    };
+    // Position in the pack of the story being edited, if it is already there
+    let editing = use_state(|| Option::<usize>::None);
+    let message = use_state(|| Option::<String>::None);
+
+    // Student preview: a validated copy of the draft, quizzed exactly like
+    // the main question screen. Nothing is recorded: the preview never
+    // touches reading history.
+    let preview = use_state(|| Option::<Story>::None);
+    let quiz = {
+        let message = message.clone();
+        use_quiz(
+            &preview,
+            Callback::from(move |msg| message.set(msg)),
+            Callback::noop(),
+        )
+    };
+
+    // --- Story fields ---
+
+    let on_title_input = edit_with_value(&draft, |st: &mut Story, v| st.title = v);
+    let on_grade_change = edit_with_value(&draft, |st: &mut Story, v| {
+        st.grade_level = v
+            .parse::<u8>()
+            .ok()
+            .map(|g| g.min(crate::adaptive::MAX_GRADE));
+    });
+    let on_tags_input = {
+        let draft_tags = draft_tags.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            draft_tags.set(input.value());
+        })
+    };
+    let on_add_paragraph = {
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut next = (*draft).clone();
+            next.paragraphs.push(String::new());
+            draft.set(next);
+        })
+    };
+    let on_add_question = {
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut next = (*draft).clone();
+            let last_paragraph = next.paragraphs.len().saturating_sub(1);
+            next.questions.push(Question {
+                paragraph_index: last_paragraph,
+                ..blank_question()
+            });
+            draft.set(next);
+        })
+    };
+
+    // --- Pack building ---
+
+    let on_add_to_pack = {
+        let story_pack = story_pack.clone();
+        let draft = draft.clone();
+        let draft_tags = draft_tags.clone();
+        let editing = editing.clone();
+        let message = message.clone();
+        Callback::from(move |_: MouseEvent| match (*draft).clone().validate() {
+            Ok((story, report)) => {
+                let mut next = (*story_pack).clone();
+                let title = story.title.clone();
+                let mut entry = PackStory {
+                    story,
+                    tags: crate::moderation::parse_list(&draft_tags),
+                    author: None,
+                    license: None,
+                };
+                match *editing {
+                    Some(i) if i < next.stories.len() => {
+                        entry.author = next.stories[i].author.take();
+                        entry.license = next.stories[i].license.take();
+                        next.stories[i] = entry;
+                    }
+                    _ => next.stories.push(entry),
+                }
+                story_pack.set(next);
+                draft.set(blank_story());
+                draft_tags.set(String::new());
+                editing.set(None);
+                let mut msg = format!("\"{}\" is in the pack.", title);
+                if !report.is_clean() {
+                    msg.push_str(&format!(" Fixed on the way: {}.", report.summary()));
+                }
+                message.set(Some(msg));
+            }
+            Err(report) => message.set(Some(format!("Not added yet: {}.", report.summary()))),
+        })
+    };
+    // Editing the loaded story: validate, then hand it back to be used
+    let on_use = {
+        let draft = draft.clone();
+        let draft_tags = draft_tags.clone();
+        let lock_story = lock_story.clone();
+        let message = message.clone();
+        let on_use_story = props.on_use_story.clone();
+        let was_locked = props.locked;
+        Callback::from(move |_: MouseEvent| {
+            let Some(on_use_story) = &on_use_story else {
+                return;
+            };
+            match (*draft).clone().validate() {
+                Ok((story, _)) => on_use_story.emit((
+                    PackStory {
+                        story,
+                        tags: crate::moderation::parse_list(&draft_tags),
+                        author: None,
+                        license: None,
+                    },
+                    (*lock_story != was_locked).then_some(*lock_story),
+                )),
+                Err(report) => {
+                    message.set(Some(format!("Not saved yet: {}.", report.summary())))
+                }
+            }
+        })
+    };
+    let on_lock_toggle = {
+        let lock_story = lock_story.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            lock_story.set(input.checked());
+        })
+    };
+
+    let on_new_story = {
+        let draft = draft.clone();
+        let draft_tags = draft_tags.clone();
+        let editing = editing.clone();
+        let preview = preview.clone();
+        let message = message.clone();
+        Callback::from(move |_: MouseEvent| {
+            draft.set(blank_story());
+            draft_tags.set(String::new());
+            editing.set(None);
+            preview.set(None);
+            message.set(None);
+        })
+    };
+    let on_edit_story = {
+        let story_pack = story_pack.clone();
+        let draft = draft.clone();
+        let draft_tags = draft_tags.clone();
+        let editing = editing.clone();
+        let preview = preview.clone();
+        let message = message.clone();
+        Callback::from(move |index: usize| {
+            if let Some(entry) = story_pack.stories.get(index) {
+                draft.set(entry.story.clone());
+                draft_tags.set(entry.tags.join(", "));
+                editing.set(Some(index));
+                preview.set(None);
+                message.set(None);
+            }
+        })
+    };
+    let on_remove_story = {
+        let story_pack = story_pack.clone();
+        let editing = editing.clone();
+        Callback::from(move |index: usize| {
+            let mut next = (*story_pack).clone();
+            if index >= next.stories.len() {
+                return;
+            }
+            next.stories.remove(index);
+            // The draft stays; it just becomes a new story if it was this one
+            match *editing {
+                Some(i) if i == index => editing.set(None),
+                Some(i) if i > index => editing.set(Some(i - 1)),
+                _ => {}
+            }
+            story_pack.set(next);
+        })
+    };
+
+    let on_pack_name_input = edit_with_value(&story_pack, |p: &mut StoryPack, v| p.name = v);
+    let on_pack_author_input = edit_with_value(&story_pack, |p: &mut StoryPack, v| p.author = v);
+    let on_pack_license_input = edit_with_value(&story_pack, |p: &mut StoryPack, v| p.license = v);
+    let on_pack_description_input =
+        edit_with_value(&story_pack, |p: &mut StoryPack, v| p.description = v);
+
+    let on_download = {
+        let story_pack = story_pack.clone();
+        let message = message.clone();
+        Callback::from(move |format: &'static str| {
+            let (text, mime) = if format == "toml" {
+                (pack::to_toml(&story_pack), "application/toml")
+            } else {
+                (pack::to_json(&story_pack), "application/json")
+            };
+            let file_name = format!("{}.{}", file_stem(&story_pack.name), format);
+            match text.and_then(|t| download(&file_name, mime, &t)) {
+                Ok(()) => message.set(Some(format!("Downloaded {}.", file_name))),
+                Err(e) => message.set(Some(format!("Could not download the pack: {}.", e))),
+            }
+        })
+    };
+    let on_download_json = {
+        let on_download = on_download.clone();
+        Callback::from(move |_: MouseEvent| on_download.emit("json"))
+    };
+    let on_download_toml = Callback::from(move |_: MouseEvent| on_download.emit("toml"));
+    let on_save = {
+        let story_pack = story_pack.clone();
+        let message = message.clone();
+        let on_save_pack = props.on_save_pack.clone();
+        Callback::from(move |_: MouseEvent| {
+            if story_pack.name.trim().is_empty() {
+                message.set(Some("Give the pack a name before saving it.".into()));
+                return;
+            }
+            let mut next = (*story_pack).clone();
+            next.name = next.name.trim().to_string();
+            on_save_pack.emit(next);
+        })
+    };
+
+    // --- Student preview ---
+
+    let on_start_preview = {
+        let draft = draft.clone();
+        let preview = preview.clone();
+        let quiz = quiz.clone();
+        let message = message.clone();
+        Callback::from(move |_: MouseEvent| match (*draft).clone().validate() {
+            Ok((story, _)) => {
+                quiz.reset(Some(&story));
+                preview.set(Some(story));
+                message.set(None);
+            }
+            Err(report) => message.set(Some(format!("Can't preview yet: {}.", report.summary()))),
+        })
+    };
+    let on_close_preview = {
+        let preview = preview.clone();
+        Callback::from(move |_: MouseEvent| preview.set(None))
+    };
+
+    // --- Rendering ---
+
+    let check = (*draft).clone().validate();
+    let reading = crate::readability::analyze(&draft);
+    let chosen_grade = draft.grade_level.unwrap_or(0);
+    let preview_done =
+        !quiz.progress.is_empty() && quiz.progress.iter().all(|qp| qp.is_correct || qp.skipped);
+    let preview_correct = quiz.progress.iter().filter(|qp| qp.is_correct).count();
+
+    let curating = props.on_use_story.is_some();
+
+    html! {
+        <section class="editor">
+            if curating {
+                <h2>{"✏️ Edit this story"}</h2>
+                <p class="sub">
+                    {"Fix an answer key, reword a choice or change anything else before students see the story. The edited story is saved to this device's story library."}
+                </p>
+            } else {
+                <h2>{"✏️ Story editor"}</h2>
+                <p class="sub">
+                    {"Write a story and its questions, try it as a student, then add it to a pack. Packs can be downloaded to share or saved to this device's story library."}
+                </p>
+            }
+
+            <div class="row">
+                <div>
+                    <label>{"Title"}</label>
+                    <input type="text" value={draft.title.clone()} oninput={on_title_input} />
+                </div>
+                <div style="max-width: 160px;">
+                    <label>{"Grade level"}</label>
+                    <select onchange={on_grade_change}>
+                        {
+                            for (0..=crate::adaptive::MAX_GRADE).map(|g| html! {
+                                <option value={g.to_string()} selected={Some(g) == draft.grade_level}>
+                                    { if g == 0 { "Kindergarten".to_string() } else { format!("Grade {}", g) } }
+                                </option>
+                            })
+                        }
+                    </select>
+                </div>
+                <div>
+                    <label>{"Tags (comma separated)"}</label>
+                    <input
+                        type="text"
+                        placeholder="animals, science"
+                        value={(*draft_tags).clone()}
+                        oninput={on_tags_input}
+                    />
+                </div>
+            </div>
+
+            <h3>{"Paragraphs"}</h3>
+            {
+                for draft.paragraphs.iter().enumerate().map(|(i, p)| {
+                    let on_input = edit_with_value(&draft, move |st: &mut Story, v| {
+                        if let Some(p) = st.paragraphs.get_mut(i) {
+                            *p = v;
+                        }
+                    });
+                    let on_remove = {
+                        let draft = draft.clone();
+                        Callback::from(move |_: MouseEvent| {
+                            let mut next = (*draft).clone();
+                            remove_paragraph(&mut next, i);
+                            draft.set(next);
+                        })
+                    };
+                    html! {
+                        <div class="editor-item">
+                            <label>{"Paragraph "}{ i + 1 }</label>
+                            <textarea rows="4" value={p.clone()} oninput={on_input} />
+                            if draft.paragraphs.len() > 1 {
+                                <button class="btn btn-small" onclick={on_remove}>{"Remove paragraph"}</button>
+                            }
+                        </div>
+                    }
+                })
+            }
+            <button class="btn btn-secondary" onclick={on_add_paragraph}>{"Add paragraph"}</button>
+
+            <h3>{"Questions"}</h3>
+            { for draft.questions.iter().enumerate().map(|(i, q)| render_question_editor(i, q, &draft)) }
+            <button class="btn btn-secondary" onclick={on_add_question}>{"Add question"}</button>
+
+            { render_check(&check) }
+            if let Some(r) = &reading {
+                <p class="sub" title={r.summary()}>
+                    { format!(
+                        "Reads like grade {} (~{}L); chosen grade {}.",
+                        r.grade_label(),
+                        r.lexile_estimate(),
+                        crate::adaptive::grade_label(chosen_grade)
+                    ) }
+                    if r.is_far_off(chosen_grade) {
+                        {" That is quite far off – consider shorter sentences or simpler words, or a different grade."}
+                    }
+                </p>
+            }
+
+            if curating {
+                <label class="choice">
+                    <input type="checkbox" checked={*lock_story} onchange={on_lock_toggle} />
+                    {"Lock this story: students replay it instead of generating new ones"}
+                </label>
+            }
+            <div>
+                if curating {
+                    <button class="btn btn-primary" onclick={on_use} disabled={check.is_err()}>
+                        {"Save & use this story"}
+                    </button>
+                } else {
+                    <button class="btn btn-primary" onclick={on_add_to_pack} disabled={check.is_err()}>
+                        { if editing.is_some() { "Update story in pack" } else { "Add story to pack" } }
+                    </button>
+                }
+                <button class="btn btn-secondary" onclick={on_start_preview} disabled={check.is_err()}>
+                    { if preview.is_some() { "Restart preview" } else { "Preview as a student" } }
+                </button>
+                if !curating {
+                    <button class="btn btn-secondary" onclick={on_new_story}>{"New story"}</button>
+                }
+            </div>
+            if let Some(msg) = &*message {
+                <div class="error">{ msg }</div>
+            }
+
+            if let Some(st) = &*preview {
+                <div class="editor-preview">
+                    <h2>{"Student preview"}</h2>
+                    { crate::render_story_box(
+                        st,
+                        st.questions.get(*quiz.current_question),
+                        *quiz.selected_choice,
+                        quiz.current_done(),
+                        &quiz.on_sentence_click,
+                        None,
+                    ) }
+                    { crate::render_question_ui(&preview, &quiz, None) }
+                    if preview_done {
+                        <p>{ format!("Preview finished: {} of {} answered correctly.", preview_correct, quiz.progress.len()) }</p>
+                    }
+                    <button class="btn btn-secondary" onclick={on_close_preview}>{"Close preview"}</button>
+                </div>
+            }
+
+            if !curating {
+                <h3>{"Pack"}</h3>
+                <div class="row">
+                    <div>
+                        <label>{"Pack name"}</label>
+                        <input type="text" value={story_pack.name.clone()} oninput={on_pack_name_input} />
+                    </div>
+                    <div>
+                        <label>{"Author"}</label>
+                        <input type="text" value={story_pack.author.clone()} oninput={on_pack_author_input} />
+                    </div>
+                    <div>
+                        <label>{"License"}</label>
+                        <input
+                            type="text"
+                            placeholder="CC BY 4.0"
+                            value={story_pack.license.clone()}
+                            oninput={on_pack_license_input}
+                        />
+                    </div>
+                </div>
+                <label>{"Description"}</label>
+                <textarea rows="2" value={story_pack.description.clone()} oninput={on_pack_description_input} />
+                if story_pack.stories.is_empty() {
+                    <p class="sub">{"No stories in the pack yet."}</p>
+                }
+                {
+                    for story_pack.stories.iter().enumerate().map(|(i, entry)| {
+                        let on_edit_story = on_edit_story.clone();
+                        let on_remove_story = on_remove_story.clone();
+                        html! {
+                            <div class="pack-row">
+                                <span>
+                                    <strong>{ &entry.story.title }</strong>
+                                    { format!(
+                                        " · Grade {} · {} questions",
+                                        crate::adaptive::grade_label(entry.story.grade_level.unwrap_or(0)),
+                                        entry.story.questions.len()
+                                    ) }
+                                    if *editing == Some(i) {
+                                        {" · editing"}
+                                    }
+                                </span>
+                                <span>
+                                    <button class="btn btn-small" onclick={Callback::from(move |_: MouseEvent| on_edit_story.emit(i))}>
+                                        {"Edit"}
+                                    </button>
+                                    <button class="btn btn-small" onclick={Callback::from(move |_: MouseEvent| on_remove_story.emit(i))}>
+                                        {"Remove"}
+                                    </button>
+                                </span>
+                            </div>
+                        }
+                    })
+                }
+                <div>
+                    <button class="btn btn-secondary" onclick={on_download_json} disabled={story_pack.stories.is_empty()}>
+                        {"Download JSON"}
+                    </button>
+                    <button class="btn btn-secondary" onclick={on_download_toml} disabled={story_pack.stories.is_empty()}>
+                        {"Download TOML"}
+                    </button>
+                    <button class="btn btn-primary" onclick={on_save} disabled={story_pack.stories.is_empty()}>
+                        {"Save pack to my library"}
+                    </button>
+                </div>
+            }
+            <button class="btn btn-secondary" onclick={props.on_close.clone()}>
+                { if curating { "Back to the story" } else { "Close editor" } }
+            </button>
+        </section>
+    }
+}
+
+// Fields for one question; which ones depend on its kind.
+fn render_question_editor(i: usize, q: &Question, draft: &UseStateHandle<Story>) -> Html {
+    let on_kind_change = edit_with_value(draft, move |st: &mut Story, v| {
+        if let (Some(q), Some(kind)) = (st.questions.get_mut(i), blank_kind(&v)) {
+            q.kind = kind;
+        }
+    });
+    let on_text_input = edit_with_value(draft, move |st: &mut Story, v| {
+        if let Some(q) = st.questions.get_mut(i) {
+            q.text = v;
+        }
+    });
+    let on_paragraph_change = edit_with_value(draft, move |st: &mut Story, v| {
+        if let (Some(q), Ok(p)) = (st.questions.get_mut(i), v.parse::<usize>()) {
+            q.paragraph_index = p;
+        }
+    });
+    let on_remove = {
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut next = (*draft).clone();
+            if i < next.questions.len() {
+                next.questions.remove(i);
+            }
+            draft.set(next);
+        })
+    };
+
+    let kind_fields = match &q.kind {
+        QuestionKind::MultipleChoice {
+            choices,
+            correct_index,
+        } => render_choice_fields(i, choices, *correct_index, draft),
+        QuestionKind::Vocabulary {
+            word,
+            choices,
+            correct_index,
+        } => {
+            let on_word_input = edit_with_value(draft, move |st: &mut Story, v| {
+                if let Some(QuestionKind::Vocabulary { word, .. }) =
+                    st.questions.get_mut(i).map(|q| &mut q.kind)
+                {
+                    *word = v;
+                }
+            });
+            html! {
+                <>
+                    <label>{"Word (as it appears in the paragraph)"}</label>
+                    <input type="text" value={word.clone()} oninput={on_word_input} />
+                    { render_choice_fields(i, choices, *correct_index, draft) }
+                </>
+            }
+        }
+        QuestionKind::TrueFalse { answer } => {
+            let on_answer_change = edit_with_value(draft, move |st: &mut Story, v| {
+                if let Some(QuestionKind::TrueFalse { answer }) =
+                    st.questions.get_mut(i).map(|q| &mut q.kind)
+                {
+                    *answer = v == "true";
+                }
+            });
+            html! {
+                <div style="max-width: 200px;">
+                    <label>{"Correct answer"}</label>
+                    <select onchange={on_answer_change}>
+                        <option value="true" selected={*answer}>{"True"}</option>
+                        <option value="false" selected={!*answer}>{"False"}</option>
+                    </select>
+                </div>
+            }
+        }
+        QuestionKind::ShortAnswer {
+            accepted_answers, ..
+        } => {
+            let on_answers_change = edit_with_value(draft, move |st: &mut Story, v| {
+                if let Some(QuestionKind::ShortAnswer {
+                    accepted_answers, ..
+                }) = st.questions.get_mut(i).map(|q| &mut q.kind)
+                {
+                    *accepted_answers = lines(&v);
+                }
+            });
+            html! {
+                <>
+                    <label>{"Accepted answers (one per line; small typos are forgiven)"}</label>
+                    <textarea rows="3" value={accepted_answers.join("\n")} onchange={on_answers_change} />
+                </>
+            }
+        }
+        QuestionKind::EvidenceSelect { correct_sentence } => {
+            let sentences = draft
+                .paragraphs
+                .get(q.paragraph_index)
+                .map(|p| split_sentences(p))
+                .unwrap_or_default();
+            let on_sentence_change = edit_with_value(draft, move |st: &mut Story, v| {
+                if let (Some(QuestionKind::EvidenceSelect { correct_sentence }), Ok(s)) = (
+                    st.questions.get_mut(i).map(|q| &mut q.kind),
+                    v.parse::<usize>(),
+                ) {
+                    *correct_sentence = s;
+                }
+            });
+            html! {
+                <>
+                    <label>{"Sentence that proves the answer"}</label>
+                    <select onchange={on_sentence_change}>
+                        {
+                            for sentences.iter().enumerate().map(|(si, s)| html! {
+                                <option value={si.to_string()} selected={si == *correct_sentence}>
+                                    { s }
+                                </option>
+                            })
+                        }
+                    </select>
+                </>
+            }
+        }
+        QuestionKind::Sequence {
+            events,
+            correct_order,
+        } => {
+            let on_events_change = edit_with_value(draft, move |st: &mut Story, v| {
+                if let Some(QuestionKind::Sequence {
+                    events,
+                    correct_order,
+                }) = st.questions.get_mut(i).map(|q| &mut q.kind)
+                {
+                    set_events_in_order(events, correct_order, lines(&v));
+                }
+            });
+            html! {
+                <>
+                    <label>{"Events in the right order (one per line; students get them mixed up)"}</label>
+                    <textarea
+                        rows="4"
+                        value={events_in_order(events, correct_order).join("\n")}
+                        onchange={on_events_change}
+                    />
+                </>
+            }
+        }
+    };
+
+    html! {
+        <div class="editor-item">
+            <div class="row">
+                <div style="max-width: 200px;">
+                    <label>{"Question "}{ i + 1 }{" type"}</label>
+                    <select onchange={on_kind_change}>
+                        {
+                            for KIND_KEYS.iter().map(|key| html! {
+                                <option value={*key} selected={*key == kind_key(&q.kind)}>
+                                    { blank_kind(key).map(|k| k.label()).unwrap_or_default() }
+                                </option>
+                            })
+                        }
+                    </select>
+                </div>
+                <div style="max-width: 200px;">
+                    <label>{"About paragraph"}</label>
+                    <select onchange={on_paragraph_change}>
+                        {
+                            for (0..draft.paragraphs.len()).map(|p| html! {
+                                <option value={p.to_string()} selected={p == q.paragraph_index}>
+                                    { format!("Paragraph {}", p + 1) }
+                                </option>
+                            })
+                        }
+                    </select>
+                </div>
+            </div>
+            <label>{ if matches!(q.kind, QuestionKind::TrueFalse { .. }) { "Statement" } else { "Question" } }</label>
+            <input type="text" value={q.text.clone()} oninput={on_text_input} />
+            { kind_fields }
+            <button class="btn btn-small" onclick={on_remove}>{"Remove question"}</button>
+        </div>
+    }
+}
+
+// Choices textarea plus the correct-choice picker (multiple choice and
+// vocabulary questions).
+fn render_choice_fields(
+    i: usize,
+    choices: &[String],
+    correct_index: usize,
+    draft: &UseStateHandle<Story>,
+) -> Html {
+    let on_choices_change = edit_with_value(draft, move |st: &mut Story, v| {
+        if let Some(
+            QuestionKind::MultipleChoice { choices, .. } | QuestionKind::Vocabulary { choices, .. },
+        ) = st.questions.get_mut(i).map(|q| &mut q.kind)
+        {
+            *choices = lines(&v);
+        }
+    });
+    let on_correct_change = edit_with_value(draft, move |st: &mut Story, v| {
+        if let (
+            Some(
+                QuestionKind::MultipleChoice { correct_index, .. }
+                | QuestionKind::Vocabulary { correct_index, .. },
+            ),
+            Ok(c),
+        ) = (
+            st.questions.get_mut(i).map(|q| &mut q.kind),
+            v.parse::<usize>(),
+        ) {
+            *correct_index = c;
+        }
+    });
+
+    html! {
+        <div class="row">
+            <div>
+                <label>{"Choices (one per line)"}</label>
+                <textarea rows="4" value={choices.join("\n")} onchange={on_choices_change} />
+            </div>
+            <div style="max-width: 240px;">
+                <label>{"Correct choice"}</label>
+                <select onchange={on_correct_change}>
+                    {
+                        for choices.iter().enumerate().map(|(c, choice)| html! {
+                            <option value={c.to_string()} selected={c == correct_index}>
+                                { choice }
+                            </option>
+                        })
+                    }
+                </select>
+            </div>
+        </div>
+    }
+}
+
+// Live validation: what would be repaired, dropped, or stops the story
+// from being used at all.
+fn render_check(check: &Result<(Story, ValidationReport), ValidationReport>) -> Html {
+    let (class, heading, report) = match check {
+        Ok((st, report)) if report.is_clean() => {
+            return html! {
+                <div class="editor-check ok">
+                    { format!(
+                        "✅ Ready: {} paragraphs, {} questions.",
+                        st.paragraphs.len(),
+                        st.questions.len()
+                    ) }
+                </div>
+            };
+        }
+        Ok((_, report)) => ("warn", "⚠️ Usable, but:", report),
+        Err(report) => ("bad", "❌ Not usable yet:", report),
+    };
+    html! {
+        <div class={classes!("editor-check", class)}>
+            <strong>{ heading }</strong>
+            <ul>
+                {
+                    for report.issues.iter().map(|issue| {
+                        let note = match issue.action() {
+                            IssueAction::Repaired => " (will be fixed automatically)",
+                            IssueAction::Warning => " (the story can still be used)",
+                            IssueAction::QuestionDropped => " (this question will be left out)",
+                            IssueAction::Unusable => "",
+                        };
+                        html! { <li>{ issue.to_string() }{ note }</li> }
+                    })
+                }
+            </ul>
+        </div>
+    }
+}
+
+// Callback for an input, textarea or select: copies the state, lets `apply`
+// change it using the element's current value, and stores the result.
+fn edit_with_value<T, E>(
+    handle: &UseStateHandle<T>,
+    apply: impl Fn(&mut T, String) + 'static,
+) -> Callback<E>
+where
+    T: Clone + 'static,
+    E: AsRef<Event> + 'static,
+{
+    let handle = handle.clone();
+    Callback::from(move |e: E| {
+        let mut next = (*handle).clone();
+        apply(&mut next, field_value(e.as_ref()));
+        handle.set(next);
+    })
+}
+
+fn field_value(e: &Event) -> String {
+    let Some(target) = e.target() else {
+        return String::new();
+    };
+    if let Some(input) = target.dyn_ref::<HtmlInputElement>() {
+        input.value()
+    } else if let Some(area) = target.dyn_ref::<HtmlTextAreaElement>() {
+        area.value()
+    } else if let Some(select) = target.dyn_ref::<HtmlSelectElement>() {
+        select.value()
+    } else {
+        String::new()
+    }
+}
+
+// Non-blank lines of a textarea, trimmed.
+fn lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+// A sequence question's events from first to last, the way the editor
+// shows them (stored events are usually mixed up).
+fn events_in_order(events: &[String], correct_order: &[usize]) -> Vec<String> {
+    if is_permutation(correct_order, events.len()) {
+        correct_order.iter().map(|&i| events[i].clone()).collect()
+    } else {
+        events.to_vec()
+    }
+}
+
+// Stores events typed first to last. With the same number of events the
+// stored (mixed-up) positions are kept, so rewording an event never changes
+// the answer key; otherwise the events are stored as typed.
+fn set_events_in_order(
+    events: &mut Vec<String>,
+    correct_order: &mut Vec<usize>,
+    typed: Vec<String>,
+) {
+    if typed.len() == events.len() && is_permutation(correct_order, events.len()) {
+        for (&slot, text) in correct_order.iter().zip(typed) {
+            events[slot] = text;
+        }
+    } else {
+        *correct_order = (0..typed.len()).collect();
+        *events = typed;
+    }
+}
+
+fn is_permutation(order: &[usize], len: usize) -> bool {
+    let mut seen = vec![false; len];
+    order.len() == len
+        && order
+            .iter()
+            .all(|&i| i < len && !std::mem::replace(&mut seen[i], true))
+}
+
+// Removes a paragraph and keeps later questions pointing at the same text.
+fn remove_paragraph(story: &mut Story, index: usize) {
+    if index >= story.paragraphs.len() || story.paragraphs.len() <= 1 {
+        return;
+    }
+    story.paragraphs.remove(index);
+    for q in &mut story.questions {
+        if q.paragraph_index > index {
+            q.paragraph_index -= 1;
+        }
+    }
+}
+
+fn blank_story() -> Story {
+    Story {
+        title: String::new(),
+        paragraphs: vec![String::new()],
+        questions: vec![blank_question()],
+        grade_level: Some(3),
+    }
+}
+
+fn blank_question() -> Question {
+    Question {
+        text: String::new(),
+        paragraph_index: 0,
+        kind: QuestionKind::MultipleChoice {
+            choices: Vec::new(),
+            correct_index: 0,
+        },
+    }
+}
+
+fn kind_key(kind: &QuestionKind) -> &'static str {
+    match kind {
+        QuestionKind::MultipleChoice { .. } => "multiple_choice",
+        QuestionKind::ShortAnswer { .. } => "short_answer",
+        QuestionKind::TrueFalse { .. } => "true_false",
+        QuestionKind::EvidenceSelect { .. } => "evidence_select",
+        QuestionKind::Sequence { .. } => "sequence",
+        QuestionKind::Vocabulary { .. } => "vocabulary",
+    }
+}
+
+// An empty question of the given kind (switching kinds starts over).
+fn blank_kind(key: &str) -> Option<QuestionKind> {
+    let kind = match key {
+        "multiple_choice" => QuestionKind::MultipleChoice {
+            choices: Vec::new(),
+            correct_index: 0,
+        },
+        "short_answer" => QuestionKind::ShortAnswer {
+            accepted_answers: Vec::new(),
+            matching: AnswerMatching::default(),
+        },
+        "true_false" => QuestionKind::TrueFalse { answer: true },
+        "evidence_select" => QuestionKind::EvidenceSelect {
+            correct_sentence: 0,
+        },
+        "sequence" => QuestionKind::Sequence {
+            events: Vec::new(),
+            correct_order: Vec::new(),
+        },
+        "vocabulary" => QuestionKind::Vocabulary {
+            word: String::new(),
+            choices: Vec::new(),
+            correct_index: 0,
+        },
+        _ => return None,
+    };
+    Some(kind)
+}
+
+// "Backyard Nature!" -> "backyard-nature"
+fn file_stem(name: &str) -> String {
+    let stem = name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if stem.is_empty() {
+        "story-pack".into()
+    } else {
+        stem
+    }
+}
+
+// Hands the text to the browser as a file download.
+fn download(file_name: &str, mime: &str, text: &str) -> Result<(), String> {
+    let parts = js_sys::Array::of1(&text.into());
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime);
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)
+        .map_err(|_| "the browser could not build the file".to_string())?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .map_err(|_| "the browser could not build the file".to_string())?;
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| "no page to download from".to_string())?;
+    let link: web_sys::HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(|_| "no page to download from".to_string())?
+        .unchecked_into();
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+    // Let the download start before the URL is released.
+    gloo_timers::callback::Timeout::new(0, move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    })
+    .forget();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn loaded_sequence_is_shown_first_to_last() {
+        let events = strings(&["Lunch", "Wake up", "Bed"]);
+        assert_eq!(
+            events_in_order(&events, &[1, 0, 2]),
+            strings(&["Wake up", "Lunch", "Bed"])
+        );
+        // A broken answer key shows the events as stored
+        assert_eq!(events_in_order(&events, &[0, 0, 2]), events);
+    }
+
+    #[test]
+    fn rewording_a_loaded_sequence_keeps_the_answer_key() {
+        let mut events = strings(&["Lunch", "Wake up", "Bed"]);
+        let mut correct_order = vec![1, 0, 2];
+        set_events_in_order(
+            &mut events,
+            &mut correct_order,
+            strings(&["Wake up early", "Lunch", "Bed"]),
+        );
+        assert_eq!(events, strings(&["Lunch", "Wake up early", "Bed"]));
+        assert_eq!(correct_order, vec![1, 0, 2]);
+        assert_eq!(
+            events_in_order(&events, &correct_order),
+            strings(&["Wake up early", "Lunch", "Bed"])
+        );
+    }
+
+    #[test]
+    fn adding_an_event_stores_them_as_typed() {
+        let mut events = strings(&["Lunch", "Wake up"]);
+        let mut correct_order = vec![1, 0];
+        set_events_in_order(
+            &mut events,
+            &mut correct_order,
+            strings(&["Wake up", "Lunch", "Bed"]),
+        );
+        assert_eq!(events, strings(&["Wake up", "Lunch", "Bed"]));
+        assert_eq!(correct_order, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,348 @@
+// E:\rust_dev\kids_story_trainer\src\fallback.rs
+//
+// Built-in stories for when no AI source can be used. The library lives in
+// fallback_stories.json (story-pack format, compiled into the app) so new
+// stories are data, not code. Each story carries topic tags; its grade is
+// `grade_level` and its length the number of paragraphs. The picker looks
+// for the story closest to what the kid typed, then to the grade and length;
+// when nothing matches the topic at all, a story is written from templates.
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+
+use crate::pack::{self, PackStory};
+use crate::template;
+use crate::{stem_word, Question, Story};
+
+const BUILTIN_PACK: &str = include_str!("fallback_stories.json");
+
+// A story this many grades from the request may still be picked when it
+// matches the topic; without a topic match only the closest grade is used.
+const TOPIC_GRADE_SLACK: u8 = 2;
+
+// Words in a typed topic that say nothing about what it is.
+const FILLER_WORDS: &[&str] = &[
+    "a", "an", "and", "the", "of", "about", "story", "stories", "on", "in", "at", "to", "for",
+    "with", "my", "our", "some",
+];
+
+// Parsed and validated once, the first time a fallback is needed.
+pub fn builtin_stories() -> &'static [PackStory] {
+    static STORIES: OnceLock<Vec<PackStory>> = OnceLock::new();
+    STORIES.get_or_init(|| {
+        pack::load_pack(BUILTIN_PACK, "fallback_stories.json")
+            .map(|(p, _)| p.stories)
+            .expect("fallback_stories.json is a valid story pack")
+    })
+}
+
+pub fn pick_fallback_story(
+    topic: &str,
+    imported: &[PackStory],
+    grade_level: u8,
+    num_paragraphs: u8,
+    num_questions: u8,
+) -> Story {
+    let mut rng = thread_rng();
+
+    let mut stories: Vec<&PackStory> = builtin_stories().iter().chain(imported).collect();
+    let distance = |entry: &PackStory| {
+        entry
+            .story
+            .grade_level
+            .map(|g| g.abs_diff(grade_level))
+            .unwrap_or(u8::MAX)
+    };
+    let closest = stories.iter().map(|e| distance(e)).min().unwrap_or(0);
+    let near_grade = closest.max(1);
+    stories.retain(|e| distance(e) <= closest.max(TOPIC_GRADE_SLACK));
+
+    // Best topic match first; then a story that still has enough questions
+    // once it is cut to length; then one within a grade of the request; then
+    // the length closest to the request so as little as possible gets cut.
+    // Ties are broken at random.
+    let desired = num_paragraphs.clamp(1, 6) as usize;
+    let desired_questions = num_questions.clamp(1, 10) as usize;
+    let topic_keys = keys(topic);
+    let rank = |entry: &PackStory| {
+        (
+            topic_score(&topic_keys, entry),
+            questions_within(&entry.story, desired) >= desired_questions,
+            distance(entry) <= near_grade,
+            std::cmp::Reverse(entry.story.paragraphs.len().abs_diff(desired)),
+        )
+    };
+    stories.shuffle(&mut rng);
+    let best = stories.into_iter().max_by_key(|e| rank(e));
+    let topic_matched = best.is_some_and(|e| topic_score(&topic_keys, e) > 0);
+    if !topic_keys.is_empty() && !topic_matched {
+        return template::generate_story(topic, grade_level, num_paragraphs, num_questions);
+    }
+    let mut story = best.map(|e| e.story.clone()).unwrap_or_default();
+
+    if story.paragraphs.len() > desired {
+        story.paragraphs.truncate(desired);
+        // Never ask about paragraphs the child was not shown.
+        story.questions.retain(|q| q.paragraph_index < desired);
+    }
+    // A story with nothing left to ask is no use; write one instead.
+    if story.questions.is_empty() {
+        return template::generate_story(topic, grade_level, num_paragraphs, num_questions);
+    }
+
+    if story.questions.len() > desired_questions {
+        let questions = std::mem::take(&mut story.questions);
+        story.questions = choose_questions(questions, desired_questions, &mut rng);
+    }
+
+    story
+}
+
+// Questions that are still about the story once it is cut to `paragraphs`.
+fn questions_within(story: &Story, paragraphs: usize) -> usize {
+    story
+        .questions
+        .iter()
+        .filter(|q| q.paragraph_index < paragraphs)
+        .count()
+}
+
+// Up to `count` questions, a different kind each where possible (stories
+// list their questions grouped by kind, so the first few would all be
+// multiple choice), kept in the story's order.
+fn choose_questions(questions: Vec<Question>, count: usize, rng: &mut impl Rng) -> Vec<Question> {
+    let mut order: Vec<usize> = (0..questions.len()).collect();
+    order.shuffle(rng);
+    let mut chosen: Vec<usize> = Vec::new();
+    for &i in &order {
+        let new_kind = !chosen
+            .iter()
+            .any(|&c| questions[c].kind.label() == questions[i].kind.label());
+        if new_kind && chosen.len() < count {
+            chosen.push(i);
+        }
+    }
+    for &i in &order {
+        if chosen.len() >= count {
+            break;
+        }
+        if !chosen.contains(&i) {
+            chosen.push(i);
+        }
+    }
+
+    questions
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| chosen.contains(i))
+        .map(|(_, q)| q)
+        .collect()
+}
+
+// How well a story fits the topic: each topic word scores 3 when it is a
+// tag, 2 when it is in the title and 1 when it only appears in the text.
+fn topic_score(topic_keys: &HashSet<String>, entry: &PackStory) -> u32 {
+    if topic_keys.is_empty() {
+        return 0;
+    }
+    let tags = keys(&entry.tags.join(" "));
+    let title = keys(&entry.story.title);
+    let text = keys(&entry.story.paragraphs.join(" "));
+    topic_keys
+        .iter()
+        .map(|k| {
+            if tags.contains(k) {
+                3
+            } else if title.contains(k) {
+                2
+            } else if text.contains(k) {
+                1
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+// `stem_word` turns "diving" into "div" but leaves "dive" alone, so a
+// trailing "e" is dropped too to make both forms match.
+pub fn word_key(word: &str) -> String {
+    let stem = stem_word(word);
+    match stem.strip_suffix('e') {
+        Some(short) if short.chars().count() >= 3 => short.to_string(),
+        _ => stem,
+    }
+}
+
+// Stemmed, lowercased words without filler ("the dinosaurs" -> "dinosaur").
+fn keys(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|w| w.to_lowercase())
+        .filter(|w| !w.is_empty() && !FILLER_WORDS.contains(&w.as_str()))
+        .map(|w| word_key(&w))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QuestionKind;
+
+    fn true_false(paragraph_index: usize) -> Question {
+        Question {
+            text: "Is it true?".into(),
+            paragraph_index,
+            kind: QuestionKind::TrueFalse { answer: true },
+        }
+    }
+
+    fn entry(title: &str, tags: &[&str], questions: Vec<Question>) -> PackStory {
+        PackStory {
+            story: Story {
+                title: title.into(),
+                paragraphs: vec![
+                    "A zorbleflux hummed.".into(),
+                    "It hummed more.".into(),
+                    "Then it slept.".into(),
+                ],
+                questions,
+                grade_level: Some(3),
+            },
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            author: None,
+            license: None,
+        }
+    }
+
+    #[test]
+    fn cutting_a_story_short_keeps_only_questions_about_what_is_shown() {
+        for _ in 0..10 {
+            let story = pick_fallback_story("garden", &[], 2, 1, 4);
+            assert_eq!(story.paragraphs.len(), 1);
+            assert!(!story.questions.is_empty());
+            assert!(story.questions.iter().all(|q| q.paragraph_index == 0));
+        }
+    }
+
+    #[test]
+    fn prefers_a_story_whose_questions_survive_the_cut() {
+        let late = entry(
+            "Late Questions",
+            &["zorbleflux"],
+            vec![true_false(2), true_false(2)],
+        );
+        let early = entry(
+            "Early Questions",
+            &["zorbleflux"],
+            vec![true_false(0), true_false(0)],
+        );
+        for _ in 0..10 {
+            let imported = [late.clone(), early.clone()];
+            let story = pick_fallback_story("zorbleflux", &imported, 3, 1, 2);
+            assert_eq!(story.title, "Early Questions");
+            assert_eq!(story.questions.len(), 2);
+        }
+    }
+
+    #[test]
+    fn writes_a_story_when_no_question_survives_the_cut() {
+        let late = entry("Late Questions", &["zorbleflux"], vec![true_false(2)]);
+        let story = pick_fallback_story("zorbleflux", &[late], 3, 1, 2);
+        assert_ne!(story.title, "Late Questions");
+        assert_eq!(story.paragraphs.len(), 1);
+        assert!(!story.questions.is_empty());
+    }
+
+    #[test]
+    fn builtin_pack_loads_and_validates_cleanly() {
+        let stories = builtin_stories();
+        assert!(!stories.is_empty());
+        for entry in stories {
+            let (_, report) = entry.story.clone().validate().expect("story validates");
+            assert!(
+                report.is_clean(),
+                "{}: {}",
+                entry.story.title,
+                report.summary()
+            );
+            assert!(entry.story.grade_level.is_some(), "{}", entry.story.title);
+            assert!(!entry.tags.is_empty(), "{}", entry.story.title);
+        }
+    }
+
+    #[test]
+    fn tag_match_outranks_title_and_text() {
+        let topic_keys = keys("zorbleflux");
+        let tagged = entry("A Quiet Day", &["zorbleflux"], vec![true_false(0)]);
+        let titled = entry("The Zorbleflux", &[], vec![true_false(0)]);
+        let mentioned = entry("A Quiet Day", &[], vec![true_false(0)]);
+        assert_eq!(topic_score(&topic_keys, &tagged), 3);
+        assert_eq!(topic_score(&topic_keys, &titled), 2);
+        assert_eq!(topic_score(&topic_keys, &mentioned), 1);
+        assert_eq!(topic_score(&keys("the story"), &tagged), 0);
+
+        for _ in 0..10 {
+            let imported = [titled.clone(), tagged.clone(), mentioned.clone()];
+            let story = pick_fallback_story("zorbleflux", &imported, 3, 3, 1);
+            assert_eq!(story.title, "A Quiet Day");
+            assert_eq!(story.paragraphs, tagged.story.paragraphs);
+        }
+    }
+
+    #[test]
+    fn unmatched_topic_gets_a_template_story() {
+        let titles: Vec<&str> = builtin_stories()
+            .iter()
+            .map(|e| e.story.title.as_str())
+            .collect();
+        let story = pick_fallback_story("volcanoes on Mars", &[], 4, 4, 3);
+        assert!(!titles.contains(&story.title.as_str()), "{}", story.title);
+        assert_eq!(story.paragraphs.len(), 4);
+        assert_eq!(story.grade_level, Some(4));
+    }
+
+    #[test]
+    fn topic_matches_pick_the_closest_grade() {
+        for _ in 0..10 {
+            assert_eq!(
+                pick_fallback_story("rain", &[], 5, 3, 4).title,
+                "The Rainy Day Coding Club"
+            );
+            assert_eq!(
+                pick_fallback_story("rain", &[], 3, 3, 4).title,
+                "The Lost Backpack on the Bus"
+            );
+        }
+    }
+
+    #[test]
+    fn topic_matches_pick_the_closest_length() {
+        let mut long = entry("Long", &["zorbleflux"], vec![true_false(0)]);
+        long.story.paragraphs = vec!["It hummed.".to_string(); 6];
+        let short = entry("Short", &["zorbleflux"], vec![true_false(0)]);
+        for _ in 0..10 {
+            let imported = [long.clone(), short.clone()];
+            let story = pick_fallback_story("zorbleflux", &imported, 3, 2, 1);
+            assert_eq!(story.title, "Short");
+            assert_eq!(story.paragraphs.len(), 2);
+        }
+    }
+
+    #[test]
+    fn questions_are_cut_to_a_mix_of_kinds_in_story_order() {
+        let questions = builtin_stories()[0].story.questions.clone();
+        let chosen = choose_questions(questions.clone(), 3, &mut thread_rng());
+        assert_eq!(chosen.len(), 3);
+        let mut labels: Vec<&str> = chosen.iter().map(|q| q.kind.label()).collect();
+        labels.sort();
+        labels.dedup();
+        assert_eq!(labels.len(), 3);
+        let positions: Vec<usize> = chosen
+            .iter()
+            .map(|c| questions.iter().position(|q| q == c).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]), "{:?}", positions);
+    }
+}
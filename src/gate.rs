@@ -0,0 +1,70 @@
+// E:\rust_dev\kids_story_trainer\src\gate.rs
+//
+// Grown-up gate for the few teacher actions that sit on screens a kid can
+// reach (unlocking or editing a locked story). The button only fires after
+// it has been held down for a few seconds, which a grown-up is told to do
+// and a kid tapping around won't do by accident.
+use gloo_timers::callback::Timeout;
+use yew::prelude::*;
+
+// How long the button has to be held down.
+pub const HOLD_MS: u32 = 3_000;
+
+#[derive(Properties, PartialEq)]
+pub struct HoldButtonProps {
+    pub label: AttrValue,
+    pub on_confirm: Callback<()>,
+    #[prop_or(AttrValue::Static("btn btn-small"))]
+    pub class: AttrValue,
+}
+
+#[function_component(HoldButton)]
+pub fn hold_button(props: &HoldButtonProps) -> Html {
+    let holding = use_state(|| false);
+    let timer = use_mut_ref(|| Option::<Timeout>::None);
+
+    let on_press = {
+        let holding = holding.clone();
+        let timer = timer.clone();
+        let on_confirm = props.on_confirm.clone();
+        Callback::from(move |_: PointerEvent| {
+            holding.set(true);
+            let holding = holding.clone();
+            let on_confirm = on_confirm.clone();
+            *timer.borrow_mut() = Some(Timeout::new(HOLD_MS, move || {
+                holding.set(false);
+                on_confirm.emit(());
+            }));
+        })
+    };
+    // Letting go early (or sliding off the button) cancels
+    let on_release = {
+        let holding = holding.clone();
+        let timer = timer.clone();
+        Callback::from(move |_: PointerEvent| {
+            if let Some(t) = timer.borrow_mut().take() {
+                t.cancel();
+            }
+            holding.set(false);
+        })
+    };
+
+    let class = classes!(
+        props.class.to_string(),
+        "hold-button",
+        holding.then_some("holding")
+    );
+    let title = format!("Grown-ups: hold for {} seconds", HOLD_MS / 1000);
+    html! {
+        <button
+            {class}
+            {title}
+            onpointerdown={on_press}
+            onpointerup={on_release.clone()}
+            onpointerleave={on_release.clone()}
+            onpointercancel={on_release}
+        >
+            { if *holding { AttrValue::Static("Keep holding...") } else { props.label.clone() } }
+        </button>
+    }
+}
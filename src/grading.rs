@@ -0,0 +1,147 @@
+// E:\rust_dev\kids_story_trainer\src\grading.rs
+use serde::{Deserialize, Serialize};
+
+// Which family of labels a scale uses. Only affects the settings picker and
+// whether the percentage is shown next to the label.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ScaleKind {
+    Letter,
+    Mastery,
+    Encouraging,
+}
+
+// Badge color for a band, mapped to the `badge-*` classes in styles.css.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BadgeTone {
+    Top,
+    Good,
+    Fair,
+    Low,
+}
+
+impl BadgeTone {
+    pub fn badge_class(self) -> &'static str {
+        match self {
+            BadgeTone::Top => "badge badge-a",
+            BadgeTone::Good => "badge badge-b",
+            BadgeTone::Fair => "badge badge-c",
+            BadgeTone::Low => "badge badge-u",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct GradeBand {
+    pub min_percent: i32,
+    pub label: String,
+    pub description: String,
+    pub tone: BadgeTone,
+}
+
+// Turns a 0–100 score into a label. Bands may be listed in any order; the
+// highest band whose `min_percent` the score reaches wins.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct GradingScale {
+    pub kind: ScaleKind,
+    pub show_percent: bool,
+    pub bands: Vec<GradeBand>,
+}
+
+impl Default for GradingScale {
+    fn default() -> Self {
+        GradingScale::letter()
+    }
+}
+
+impl GradingScale {
+    pub const PRESETS: [ScaleKind; 3] = [ScaleKind::Letter, ScaleKind::Mastery, ScaleKind::Encouraging];
+
+    pub fn preset(kind: ScaleKind) -> GradingScale {
+        match kind {
+            ScaleKind::Letter => GradingScale::letter(),
+            ScaleKind::Mastery => GradingScale::mastery(),
+            ScaleKind::Encouraging => GradingScale::encouraging(),
+        }
+    }
+
+    pub fn letter() -> GradingScale {
+        GradingScale {
+            kind: ScaleKind::Letter,
+            show_percent: true,
+            bands: vec![
+                band(90, "A", "Excellent", BadgeTone::Top),
+                band(80, "B", "Good", BadgeTone::Good),
+                band(70, "C", "Needs Practice", BadgeTone::Fair),
+                band(0, "Unsatisfactory", "Keep Working!", BadgeTone::Low),
+            ],
+        }
+    }
+
+    // Standards-based 1–4 mastery levels.
+    pub fn mastery() -> GradingScale {
+        GradingScale {
+            kind: ScaleKind::Mastery,
+            show_percent: true,
+            bands: vec![
+                band(90, "4", "Exceeds the standard", BadgeTone::Top),
+                band(75, "3", "Meets the standard", BadgeTone::Good),
+                band(50, "2", "Approaching the standard", BadgeTone::Fair),
+                band(0, "1", "Beginning", BadgeTone::Low),
+            ],
+        }
+    }
+
+    // No grades or percentages, just a friendly word for young readers.
+    pub fn encouraging() -> GradingScale {
+        GradingScale {
+            kind: ScaleKind::Encouraging,
+            show_percent: false,
+            bands: vec![
+                band(90, "Superstar Reader", "You nailed it!", BadgeTone::Top),
+                band(70, "Great Job", "You're getting stronger every story.", BadgeTone::Good),
+                band(40, "Nice Try", "Reading it again will help.", BadgeTone::Fair),
+                band(0, "Keep Going", "Every story makes you a better reader.", BadgeTone::Fair),
+            ],
+        }
+    }
+
+    pub fn grade(&self, score_percent: i32) -> Option<&GradeBand> {
+        self.bands
+            .iter()
+            .filter(|b| score_percent >= b.min_percent)
+            .max_by_key(|b| b.min_percent)
+    }
+}
+
+impl ScaleKind {
+    pub fn key(self) -> &'static str {
+        match self {
+            ScaleKind::Letter => "letter",
+            ScaleKind::Mastery => "mastery",
+            ScaleKind::Encouraging => "encouraging",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<ScaleKind> {
+        GradingScale::PRESETS.into_iter().find(|k| k.key() == key)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScaleKind::Letter => "Letter grades (A/B/C)",
+            ScaleKind::Mastery => "Mastery levels (1–4)",
+            ScaleKind::Encouraging => "Encouraging words (no grades)",
+        }
+    }
+}
+
+fn band(min_percent: i32, label: &str, description: &str, tone: BadgeTone) -> GradeBand {
+    GradeBand {
+        min_percent,
+        label: label.into(),
+        description: description.into(),
+        tone,
+    }
+}
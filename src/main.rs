@@ -1,23 +1,51 @@
 // E:\rust_dev\kids_story_trainer\src\main.rs
-use gloo_net::http::Request;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen_futures::spawn_local;
-use web_sys::{Event, HtmlInputElement};
+use web_sys::{Event, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
 use yew::events::{InputEvent, MouseEvent};
 use yew::prelude::*;
 use yew::TargetCast;
 
-// 🔗 Your deployed Cloudflare Worker URL
-const WORKER_URL: &str =
-    "https://kids-story-worker.mikegyver.workers.dev/api/story";
-
-#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+mod adaptive;
+mod config;
+mod editor;
+mod fallback;
+mod gate;
+mod grading;
+mod moderation;
+mod pack;
+mod progress;
+mod readability;
+mod provider;
+mod quiz;
+mod scoring;
+mod speech;
+mod storage;
+mod stream;
+mod template;
+mod validate;
+
+use adaptive::DifficultyLevel;
+use config::AppConfig;
+use editor::StoryEditor;
+use fallback::pick_fallback_story;
+use gate::HoldButton;
+use grading::{GradeBand, GradingScale, ScaleKind};
+use moderation::{BlockedTopic, ContentRules, ScreeningAction, TopicFilter, TopicVerdict};
+use pack::{PackStory, StoryPack};
+use provider::{CancelToken, ProviderChain, StoryRequest};
+use quiz::{use_quiz, Quiz};
+use scoring::{Accuracy, ScoringPolicy};
+use storage::{LoadError, SavedData, SessionRecord};
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug, Default)]
 struct Story {
     title: String,
     paragraphs: Vec<String>,
     questions: Vec<Question>,
+    // Grade the story was written for (0 = kindergarten), if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    grade_level: Option<u8>,
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
@@ -27,6 +55,69 @@ enum QuestionKind {
         choices: Vec<String>,
         correct_index: usize,
     },
+    ShortAnswer {
+        accepted_answers: Vec<String>,
+        #[serde(default)]
+        matching: AnswerMatching,
+    },
+    TrueFalse {
+        answer: bool,
+    },
+    // Kid taps the sentence (within `paragraph_index`) that supports the
+    // answer. Sentences are split with `split_sentences`.
+    EvidenceSelect {
+        correct_sentence: usize,
+    },
+    // `events` are shown in the listed order (so list them scrambled);
+    // `correct_order` holds indices into `events` from first to last.
+    // `paragraph_index` is the last paragraph any event comes from.
+    Sequence {
+        events: Vec<String>,
+        correct_order: Vec<usize>,
+    },
+    // Meaning of `word` as used in paragraph `paragraph_index`; the word is
+    // highlighted in the story box while the question is shown.
+    Vocabulary {
+        word: String,
+        choices: Vec<String>,
+        correct_index: usize,
+    },
+}
+
+// How forgiving short-answer grading is. Every field is optional in the
+// Worker JSON; missing fields use the kid-friendly defaults below.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+struct AnswerMatching {
+    case_sensitive: bool,
+    ignore_punctuation: bool,
+    stem_words: bool,
+    max_typos: usize,
+}
+
+impl Default for AnswerMatching {
+    fn default() -> Self {
+        AnswerMatching {
+            case_sensitive: false,
+            ignore_punctuation: true,
+            stem_words: true,
+            max_typos: 1,
+        }
+    }
+}
+
+impl QuestionKind {
+    // Short name used when summarizing results by question type.
+    fn label(&self) -> &'static str {
+        match self {
+            QuestionKind::MultipleChoice { .. } => "Multiple choice",
+            QuestionKind::ShortAnswer { .. } => "Short answer",
+            QuestionKind::TrueFalse { .. } => "True or false",
+            QuestionKind::EvidenceSelect { .. } => "Find the sentence",
+            QuestionKind::Sequence { .. } => "Put in order",
+            QuestionKind::Vocabulary { .. } => "Vocabulary",
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
@@ -37,11 +128,46 @@ struct Question {
     kind: QuestionKind,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
 struct QuestionProgress {
     attempts: u32,
     is_correct: bool,
     skipped: bool,
+    // Best credit earned so far, 0.0..=1.0. Only sequence questions can
+    // land in between.
+    credit: f32,
+    // Credit earned on the first attempt alone (for first-try scoring).
+    first_try_credit: f32,
+}
+
+impl QuestionProgress {
+    fn new() -> Self {
+        QuestionProgress {
+            attempts: 0,
+            is_correct: false,
+            skipped: false,
+            credit: 0.0,
+            first_try_credit: 0.0,
+        }
+    }
+}
+
+// Adult-facing options that shape how a quiz is scored and graded.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+struct AppSettings {
+    scoring: ScoringPolicy,
+    grading: GradingScale,
+    // Opt-in: keep finished sessions in this browser's localStorage.
+    save_history: bool,
+    // Let recent results pick the next story's grade level and length.
+    adaptive_difficulty: bool,
+    // Teacher-editable topic blocklist/allowlist.
+    topic_filter: TopicFilter,
+    // What to look for in AI stories, and what to do when it's found.
+    content_rules: ContentRules,
+    // Re-request AI stories that read far from the chosen grade.
+    check_reading_level: bool,
 }
 
 #[derive(Clone, PartialEq)]
@@ -51,22 +177,161 @@ enum AppPhase {
     ReadStory,
     Questioning,
     Finished,
+    Dashboard,
+    Editor,
 }
 
 #[function_component(App)]
 fn app() -> Html {
     let topic = use_state(|| "".to_string());
     let num_paragraphs = use_state(|| 3u8);
-    let story = use_state(|| Option::<Story>::None);
-    let question_progress = use_state(|| Vec::<QuestionProgress>::new());
-    let current_question = use_state(|| 0usize);
-    let phase = use_state(|| AppPhase::SelectTopic);
-    let use_ai = use_state(|| false); // true if Cloudflare AI used
+    let grade_level = use_state(|| 5u8); // 0 = kindergarten
+    let num_questions = use_state(|| 4u8);
+    // A story a grown-up locked: students start on it and replay it
+    let locked_story = use_state(storage::load_locked_story);
+    let story = {
+        let locked_story = locked_story.clone();
+        use_state(move || (*locked_story).clone())
+    };
+    let phase = {
+        let locked = locked_story.is_some();
+        use_state(move || if locked { AppPhase::ReadStory } else { AppPhase::SelectTopic })
+    };
+    let use_ai = use_state(|| false); // true if an AI provider wrote the story
+    let story_source = use_state(String::new); // label of the provider that did
     let is_loading = use_state(|| false);
-    let error = use_state(|| Option::<String>::None);
+    // Token for the story request in flight; replaced by every new request.
+    let pending_request = use_mut_ref(CancelToken::default);
+    // Saved settings + history (only present if a grown-up opted in)
+    let startup = use_memo((), |_| storage::load());
+    // Imported story packs (teacher content, kept separately from history)
+    let library_load = use_memo((), |_| storage::load_library());
+    let error = {
+        let startup = startup.clone();
+        let library_load = library_load.clone();
+        use_state(move || {
+            let history_error = startup.as_ref().as_ref().err().map(|e| e.to_string());
+            let library_error = match &*library_load {
+                Err(LoadError::Corrupt(e)) => Some(format!(
+                    "Saved story packs could not be read ({}); they were left untouched.",
+                    e
+                )),
+                Err(LoadError::FromNewerVersion(v)) => Some(format!(
+                    "Saved story packs are from a newer version of the app (v{}); they were left untouched.",
+                    v
+                )),
+                _ => None,
+            };
+            history_error.or(library_error)
+        })
+    };
+    let settings = {
+        let startup = startup.clone();
+        use_state(move || match &*startup {
+            Ok(Some(saved)) => saved.settings.clone(),
+            _ => AppSettings::default(),
+        })
+    };
+    let history = {
+        let startup = startup.clone();
+        use_state(move || match &*startup {
+            Ok(Some(saved)) => saved.sessions.clone(),
+            _ => Vec::<SessionRecord>::new(),
+        })
+    };
+    let library = use_state(move || library_load.as_ref().clone().unwrap_or_default());
+    // Topics the filter stopped, for the teacher
+    let blocked_topics = use_state(move || match &*startup {
+        Ok(Some(saved)) => saved.blocked_topics.clone(),
+        _ => Vec::<BlockedTopic>::new(),
+    });
+    // Backend profiles come from config.json (if any), loaded once on startup
+    let app_config = use_state(AppConfig::default);
+    let backend_name = use_state(|| AppConfig::default().default_profile);
+    {
+        let app_config = app_config.clone();
+        let backend_name = backend_name.clone();
+        let error = error.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                let (config, warning) = config::load().await;
+                backend_name.set(config.default_profile.clone());
+                app_config.set(config);
+                if warning.is_some() {
+                    error.set(warning);
+                }
+            });
+            || ()
+        });
+    }
+    let active_profile = app_config.profile(&backend_name);
+
+    // Level the loaded story was requested at (recorded with the session)
+    let story_level = use_state(DifficultyLevel::default);
+
+    // Level for the next story: adaptive recommendation, or the manual inputs
+    let manual_level = DifficultyLevel {
+        grade_level: *grade_level,
+        num_paragraphs: *num_paragraphs,
+        ..DifficultyLevel::default()
+    };
+    let recommendation = adaptive::recommend(&history, manual_level);
+    let next_level = if settings.adaptive_difficulty {
+        recommendation.level
+    } else {
+        manual_level
+    };
 
-    // UI state for current answer (MC only)
-    let selected_choice = use_state(|| Option::<usize>::None);
+    // Question-by-question state; finishing the last question records the
+    // session and shows the results
+    let quiz = {
+        let on_message = {
+            let error = error.clone();
+            Callback::from(move |msg| error.set(msg))
+        };
+        let on_finished = {
+            let story = story.clone();
+            let blocked_topics = blocked_topics.clone();
+            let phase = phase.clone();
+            let use_ai = use_ai.clone();
+            let story_level = story_level.clone();
+            let settings = settings.clone();
+            let history = history.clone();
+            let error = error.clone();
+            Callback::from(move |qp_vec: Vec<QuestionProgress>| {
+                if let Some(st) = &*story {
+                    record_session(
+                        st,
+                        &qp_vec,
+                        *use_ai,
+                        *story_level,
+                        &settings,
+                        &history,
+                        &blocked_topics,
+                        &error,
+                    );
+                }
+                phase.set(AppPhase::Finished);
+            })
+        };
+        use_quiz(&story, on_message, on_finished)
+    };
+
+    // Read-aloud for this session only (never saved); None means narration
+    // is not allowed, so it can stay off during reading assessments.
+    let narration = use_state(|| Option::<speech::Narration>::None);
+    let voices = use_state(speech::voices);
+    {
+        let voices = voices.clone();
+        use_effect_with((), move |_| {
+            speech::watch_voices(Callback::from(move |list| voices.set(list)))
+        });
+    }
+    // Stop reading when the screen moves on to something else
+    use_effect_with(((*phase).clone(), *quiz.current_question), |_| {
+        speech::stop();
+        || ()
+    });
 
     // Input handlers
     let on_topic_input = {
@@ -88,271 +353,657 @@ fn app() -> Html {
         })
     };
 
+    let on_grade_change = {
+        let grade_level = grade_level.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Ok(v) = select.value().parse::<u8>() {
+                grade_level.set(v.min(adaptive::MAX_GRADE));
+            }
+        })
+    };
+
+    let on_questions_input = {
+        let num_questions = num_questions.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(v) = input.value().parse::<u8>() {
+                let v = v.clamp(1, 10);
+                num_questions.set(v);
+            }
+        })
+    };
+
+    let on_scoring_change = {
+        let settings = settings.clone();
+        let history = history.clone();
+        let blocked_topics = blocked_topics.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(policy) = ScoringPolicy::from_key(&select.value()) {
+                let mut next = (*settings).clone();
+                next.scoring = policy;
+                if let Some(msg) = persist(&next, &history, &blocked_topics) {
+                    error.set(Some(msg));
+                }
+                settings.set(next);
+            }
+        })
+    };
+
+    let on_grading_change = {
+        let settings = settings.clone();
+        let history = history.clone();
+        let blocked_topics = blocked_topics.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(kind) = ScaleKind::from_key(&select.value()) {
+                let mut next = (*settings).clone();
+                next.grading = GradingScale::preset(kind);
+                if let Some(msg) = persist(&next, &history, &blocked_topics) {
+                    error.set(Some(msg));
+                }
+                settings.set(next);
+            }
+        })
+    };
+
+    // Opt in/out of saving history on this device. Opting out deletes the
+    // save file but keeps this session's results on screen.
+    let on_save_history_toggle = {
+        let settings = settings.clone();
+        let history = history.clone();
+        let blocked_topics = blocked_topics.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut next = (*settings).clone();
+            next.save_history = input.checked();
+            if next.save_history {
+                if let Some(msg) = persist(&next, &history, &blocked_topics) {
+                    error.set(Some(msg));
+                }
+            } else {
+                storage::clear();
+            }
+            settings.set(next);
+        })
+    };
+
+    // Progress dashboard; remembers where to go back to
+    let dashboard_return = use_state(|| AppPhase::SelectTopic);
+    let on_open_dashboard = {
+        let phase = phase.clone();
+        let dashboard_return = dashboard_return.clone();
+        Callback::from(move |_: MouseEvent| {
+            dashboard_return.set((*phase).clone());
+            phase.set(AppPhase::Dashboard);
+        })
+    };
+    let on_close_dashboard = {
+        let phase = phase.clone();
+        let dashboard_return = dashboard_return.clone();
+        Callback::from(move |_: MouseEvent| {
+            phase.set((*dashboard_return).clone());
+        })
+    };
+
+    let on_adaptive_toggle = {
+        let settings = settings.clone();
+        let history = history.clone();
+        let blocked_topics = blocked_topics.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut next = (*settings).clone();
+            next.adaptive_difficulty = input.checked();
+            if let Some(msg) = persist(&next, &history, &blocked_topics) {
+                error.set(Some(msg));
+            }
+            settings.set(next);
+        })
+    };
+
+    let on_narration_toggle = {
+        let narration = narration.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if !input.checked() {
+                speech::stop();
+            }
+            narration.set(input.checked().then(speech::Narration::default));
+        })
+    };
+
+    let on_voice_change = {
+        let narration = narration.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(current) = &*narration {
+                let value = select.value();
+                narration.set(Some(speech::Narration {
+                    voice: (!value.is_empty()).then_some(value),
+                    ..current.clone()
+                }));
+            }
+        })
+    };
+
+    let on_rate_change = {
+        let narration = narration.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let (Some(current), Ok(rate)) = (&*narration, select.value().parse::<f32>()) {
+                narration.set(Some(speech::Narration {
+                    rate,
+                    ..current.clone()
+                }));
+            }
+        })
+    };
+
+    // The word lists are not for children's eyes: they stay hidden until a
+    // grown-up asks for them (not saved, so they are hidden again next time)
+    let show_word_lists = use_state(|| false);
+    let on_toggle_word_lists = {
+        let show_word_lists = show_word_lists.clone();
+        Callback::from(move |_: MouseEvent| show_word_lists.set(!*show_word_lists))
+    };
+
+    // Teacher edits to the topic lists (one entry per line)
+    let on_topic_filter_change = {
+        let settings = settings.clone();
+        let history = history.clone();
+        let blocked_topics = blocked_topics.clone();
+        let error = error.clone();
+        Callback::from(move |filter: TopicFilter| {
+            let mut next = (*settings).clone();
+            next.topic_filter = filter;
+            if let Some(msg) = persist(&next, &history, &blocked_topics) {
+                error.set(Some(msg));
+            }
+            settings.set(next);
+        })
+    };
+    let on_blocked_list_change = {
+        let settings = settings.clone();
+        let on_topic_filter_change = on_topic_filter_change.clone();
+        Callback::from(move |e: Event| {
+            let area: HtmlTextAreaElement = e.target_unchecked_into();
+            let mut filter = settings.topic_filter.clone();
+            filter.blocked = moderation::parse_list(&area.value());
+            on_topic_filter_change.emit(filter);
+        })
+    };
+    let on_allowed_list_change = {
+        let settings = settings.clone();
+        let on_topic_filter_change = on_topic_filter_change.clone();
+        Callback::from(move |e: Event| {
+            let area: HtmlTextAreaElement = e.target_unchecked_into();
+            let mut filter = settings.topic_filter.clone();
+            filter.allowed = moderation::parse_list(&area.value());
+            on_topic_filter_change.emit(filter);
+        })
+    };
+    let on_reset_topic_lists = {
+        let on_topic_filter_change = on_topic_filter_change.clone();
+        Callback::from(move |_: MouseEvent| on_topic_filter_change.emit(TopicFilter::default()))
+    };
+
+    let on_content_rules_change = {
+        let settings = settings.clone();
+        let history = history.clone();
+        let blocked_topics = blocked_topics.clone();
+        let error = error.clone();
+        Callback::from(move |rules: ContentRules| {
+            let mut next = (*settings).clone();
+            next.content_rules = rules;
+            if let Some(msg) = persist(&next, &history, &blocked_topics) {
+                error.set(Some(msg));
+            }
+            settings.set(next);
+        })
+    };
+    let on_screening_action_change = {
+        let settings = settings.clone();
+        let on_content_rules_change = on_content_rules_change.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(action) = ScreeningAction::from_key(&select.value()) {
+                let mut rules = settings.content_rules.clone();
+                rules.action = action;
+                on_content_rules_change.emit(rules);
+            }
+        })
+    };
+    let on_custom_words_change = {
+        let settings = settings.clone();
+        let on_content_rules_change = on_content_rules_change.clone();
+        Callback::from(move |e: Event| {
+            let area: HtmlTextAreaElement = e.target_unchecked_into();
+            let mut rules = settings.content_rules.clone();
+            rules.custom = moderation::parse_list(&area.value());
+            on_content_rules_change.emit(rules);
+        })
+    };
+
+    let on_reading_level_toggle = {
+        let settings = settings.clone();
+        let history = history.clone();
+        let blocked_topics = blocked_topics.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut next = (*settings).clone();
+            next.check_reading_level = input.checked();
+            if let Some(msg) = persist(&next, &history, &blocked_topics) {
+                error.set(Some(msg));
+            }
+            settings.set(next);
+        })
+    };
+
+    // Import a story pack file (.json or .toml) into the library
+    let on_import_pack = {
+        let library = library.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            input.set_value("");
+            let library = library.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let file_name = file.name();
+                let text = match wasm_bindgen_futures::JsFuture::from(file.text()).await {
+                    Ok(text) => text.as_string().unwrap_or_default(),
+                    Err(_) => {
+                        error.set(Some(format!("Could not read {}.", file_name)));
+                        return;
+                    }
+                };
+                match pack::load_pack(&text, &file_name) {
+                    Ok((story_pack, notes)) => {
+                        let mut msg = format!(
+                            "Imported \"{}\" ({} stories).",
+                            story_pack.name,
+                            story_pack.story_count()
+                        );
+                        if !notes.is_empty() {
+                            msg.push_str(&format!(" {}.", notes.join("; ")));
+                        }
+                        let mut next = (*library).clone();
+                        pack::merge(&mut next, story_pack);
+                        if let Err(e) = storage::save_library(&next) {
+                            msg.push_str(&format!(" It will be gone after a reload: {}.", e));
+                        }
+                        library.set(next);
+                        error.set(Some(msg));
+                    }
+                    Err(e) => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+    let on_remove_pack = {
+        let library = library.clone();
+        let error = error.clone();
+        Callback::from(move |index: usize| {
+            let mut next = (*library).clone();
+            if index < next.len() {
+                next.remove(index);
+            }
+            if let Err(e) = storage::save_library(&next) {
+                error.set(Some(format!("Could not update the story library: {}.", e)));
+            }
+            library.set(next);
+        })
+    };
+
+    // Teacher story editor; a saved pack joins the library like an import.
+    // `editor_story` is set when editing the loaded story from the reading
+    // screen instead of writing a new pack.
+    let editor_story = use_state(|| Option::<Story>::None);
+    let on_open_editor = {
+        let editor_story = editor_story.clone();
+        let phase = phase.clone();
+        Callback::from(move |_: MouseEvent| {
+            editor_story.set(None);
+            phase.set(AppPhase::Editor);
+        })
+    };
+    let on_edit_current_story = {
+        let editor_story = editor_story.clone();
+        let story = story.clone();
+        let story_level = story_level.clone();
+        let phase = phase.clone();
+        Callback::from(move |_| {
+            let Some(mut st) = (*story).clone() else {
+                return;
+            };
+            st.grade_level = st.grade_level.or(Some(story_level.grade_level));
+            editor_story.set(Some(st));
+            phase.set(AppPhase::Editor);
+        })
+    };
+    let on_close_editor = {
+        let editor_story = editor_story.clone();
+        let phase = phase.clone();
+        Callback::from(move |_: MouseEvent| {
+            if editor_story.is_some() {
+                phase.set(AppPhase::ReadStory);
+            } else {
+                phase.set(AppPhase::SelectTopic);
+            }
+        })
+    };
+    // Edited story: saved to the library and loaded for the kid. The lock
+    // only changes when the grown-up changed the lock checkbox; a locked
+    // story that was edited stays locked in its edited form.
+    let on_use_edited_story = {
+        let library = library.clone();
+        let locked_story = locked_story.clone();
+        let story = story.clone();
+        let quiz = quiz.clone();
+        let phase = phase.clone();
+        let error = error.clone();
+        Callback::from(move |(entry, lock_change): (PackStory, Option<bool>)| {
+            let st = entry.story.clone();
+            let mut msg = format!("Saved \"{}\" to the story library.", st.title);
+            let mut next = (*library).clone();
+            pack::save_story(&mut next, pack::EDITED_PACK_NAME, entry);
+            if let Err(e) = storage::save_library(&next) {
+                msg.push_str(&format!(" It will be gone after a reload: {}.", e));
+            }
+            library.set(next);
+
+            let lock = lock_change.unwrap_or(locked_story.is_some());
+            let locked = lock.then(|| st.clone());
+            if lock_change.is_some() || lock {
+                if let Err(e) = storage::save_locked_story(locked.as_ref()) {
+                    msg.push_str(&format!(" The lock could not be saved: {}.", e));
+                }
+                locked_story.set(locked);
+            }
+            match lock_change {
+                Some(true) => msg.push_str(" Students will replay this story until it is unlocked."),
+                Some(false) => msg.push_str(" The story is no longer locked."),
+                None if lock => msg.push_str(" It is still the locked story."),
+                None => {}
+            }
+
+            quiz.reset(Some(&st));
+            story.set(Some(st));
+            error.set(Some(msg));
+            phase.set(AppPhase::ReadStory);
+        })
+    };
+    let on_unlock_story = {
+        let locked_story = locked_story.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            if let Err(e) = storage::save_locked_story(None) {
+                error.set(Some(format!("Could not unlock the story: {}.", e)));
+                return;
+            }
+            locked_story.set(None);
+        })
+    };
+    let on_save_editor_pack = {
+        let library = library.clone();
+        let phase = phase.clone();
+        let error = error.clone();
+        Callback::from(move |story_pack: StoryPack| {
+            let mut msg = format!(
+                "Saved \"{}\" ({} stories) to the story library.",
+                story_pack.name,
+                story_pack.story_count()
+            );
+            let mut next = (*library).clone();
+            pack::merge(&mut next, story_pack);
+            if let Err(e) = storage::save_library(&next) {
+                msg.push_str(&format!(" It will be gone after a reload: {}.", e));
+            }
+            library.set(next);
+            error.set(Some(msg));
+            phase.set(AppPhase::SelectTopic);
+        })
+    };
+
+    let on_backend_change = {
+        let backend_name = backend_name.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            backend_name.set(select.value());
+        })
+    };
+
+    let on_clear_data = {
+        let settings = settings.clone();
+        let history = history.clone();
+        let blocked_topics = blocked_topics.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            storage::clear();
+            let mut next = (*settings).clone();
+            next.save_history = false;
+            settings.set(next);
+            history.set(Vec::new());
+            blocked_topics.set(Vec::new());
+            error.set(None);
+        })
+    };
+
     let reset_quiz_state = {
-        let question_progress = question_progress.clone();
-        let current_question = current_question.clone();
-        let selected_choice = selected_choice.clone();
+        let story = story.clone();
+        let quiz = quiz.clone();
         let phase = phase.clone();
         let error = error.clone();
 
         Callback::from(move |_| {
             error.set(None);
-            selected_choice.set(None);
-            current_question.set(0);
-            question_progress.set(Vec::new());
+            quiz.reset(story.as_ref());
             phase.set(AppPhase::ReadStory);
         })
     };
 
-    // Generate story via Cloudflare Worker AI, with fallback to local stories
+    // Generate story via the configured providers, with fallback to local stories
     let on_generate_story = {
         let topic = topic.clone();
-        let num_paragraphs = num_paragraphs.clone();
+        let locked_story = locked_story.clone();
+        let library = library.clone();
+        let settings = settings.clone();
+        let history = history.clone();
+        let blocked_topics = blocked_topics.clone();
+        let app_config = app_config.clone();
+        let backend_name = backend_name.clone();
+        let story_source = story_source.clone();
+        let pending_request = pending_request.clone();
+        let num_questions = num_questions.clone();
+        let story_level = story_level.clone();
         let story_state = story.clone();
-        let question_progress = question_progress.clone();
-        let current_question = current_question.clone();
+        let quiz = quiz.clone();
         let phase = phase.clone();
         let use_ai = use_ai.clone();
         let is_loading = is_loading.clone();
         let error = error.clone();
-        let selected_choice = selected_choice.clone();
 
         Callback::from(move |_| {
+            if locked_story.is_some() {
+                return;
+            }
             let topic_value = (*topic).trim().to_string();
             if topic_value.is_empty() {
                 error.set(Some("Please enter a story topic first.".into()));
                 return;
             }
+            // Checked on the device, before anything is sent anywhere
+            if let TopicVerdict::Blocked { matched } = settings.topic_filter.check(&topic_value) {
+                let mut log = (*blocked_topics).clone();
+                log.push(BlockedTopic {
+                    topic: topic_value,
+                    matched,
+                    at: storage::now_ms(),
+                });
+                if log.len() > moderation::MAX_BLOCKED_LOG {
+                    log.remove(0);
+                }
+                let _ = persist(&settings, &history, &log);
+                blocked_topics.set(log);
+                error.set(Some(moderation::friendly_message()));
+                return;
+            }
+
+            // A newer request always wins over one still in flight.
+            pending_request.borrow().cancel();
+            let cancel = CancelToken::default();
+            *pending_request.borrow_mut() = cancel.clone();
 
             error.set(None);
             is_loading.set(true);
             phase.set(AppPhase::LoadingStory);
-            selected_choice.set(None);
-            current_question.set(0);
-            question_progress.set(Vec::new());
+            quiz.reset(None);
+            story_state.set(None);
 
             let topic_for_async = topic_value.clone();
-            let num_paragraphs_for_async = *num_paragraphs;
+            let level = next_level;
+            let imported = pack::library_stories(&library);
+            let chain = ProviderChain::from_config(&app_config, &backend_name, imported.clone());
+            let content_rules = settings.content_rules.clone();
+            let check_reading_level = settings.check_reading_level;
+            let num_questions_for_async = *num_questions;
+            story_level.set(level);
             let story_state = story_state.clone();
-            let question_progress = question_progress.clone();
-            let current_question = current_question.clone();
+            let quiz = quiz.clone();
             let phase = phase.clone();
             let use_ai = use_ai.clone();
+            let story_source = story_source.clone();
             let is_loading = is_loading.clone();
             let error = error.clone();
 
             spawn_local(async move {
-                let payload = serde_json::json!({
-                    "topic": topic_for_async,
-                    "gradeLevel": 5,
-                    "numParagraphs": num_paragraphs_for_async,
-                    "numQuestions": 4
-                });
-
-                let mut error_msg: Option<String> = None;
-
-                let story_res: Option<Story> =
-                    match Request::post(WORKER_URL).json(&payload) {
-                        Err(e) => {
-                            error_msg = Some(format!(
-                                "Could not build AI request; using fallback. ({})",
-                                e
-                            ));
-                            None
+                let request = StoryRequest {
+                    topic: topic_for_async,
+                    level,
+                    num_questions: num_questions_for_async,
+                };
+                // Streamed paragraphs show up as they arrive, already masked
+                // (the full story is screened again once it is complete)
+                let on_progress = {
+                    let story_state = story_state.clone();
+                    let cancel = cancel.clone();
+                    let content_rules = content_rules.clone();
+                    move |partial: &Story| {
+                        if !cancel.is_cancelled() {
+                            story_state.set(Some(content_rules.mask(partial.clone())));
                         }
-                        Ok(req) => match req.send().await {
-                            Ok(resp) if resp.status() == 200 => {
-                                match resp.json::<Story>().await {
-                                    Ok(st) => Some(st),
-                                    Err(e) => {
-                                        error_msg = Some(format!(
-                                            "AI response parse error; using fallback. ({})",
-                                            e
-                                        ));
-                                        None
-                                    }
-                                }
-                            }
-                            Ok(resp) => {
-                                error_msg = Some(format!(
-                                    "AI story request failed with status {}; using fallback.",
-                                    resp.status()
-                                ));
-                                None
-                            }
-                            Err(e) => {
-                                error_msg = Some(format!(
-                                    "Could not reach AI Worker; using fallback. ({})",
-                                    e
-                                ));
-                                None
+                    }
+                };
+                let mut notes: Vec<String> = Vec::new();
+                let mut regenerations_left = if check_reading_level {
+                    readability::MAX_REGENERATIONS
+                } else {
+                    0
+                };
+                let (final_story, used_ai, source) = loop {
+                    let Some(result) = chain.generate(&request, &cancel, &on_progress).await else {
+                        return;
+                    };
+                    notes.extend(result.notes);
+                    if !result.used_ai {
+                        break (result.story, false, result.source);
+                    }
+
+                    let st = match content_rules.apply(result.story, level.grade_level) {
+                        Ok((st, report)) => {
+                            if !report.is_clean() {
+                                notes.push(format!("Some words were hidden ({})", report.summary()));
                             }
-                        },
+                            st
+                        }
+                        Err(report) => {
+                            notes.push(format!("AI story was not used ({})", report.summary()));
+                            let fallback = pick_fallback_story(
+                                &request.topic,
+                                &imported,
+                                level.grade_level,
+                                level.num_paragraphs,
+                                num_questions_for_async,
+                            );
+                            break (fallback, false, result.source);
+                        }
                     };
 
-                let final_story = if let Some(st) = story_res {
-                    use_ai.set(true);
-                    st
-                } else {
-                    use_ai.set(false);
-                    pick_fallback_story(num_paragraphs_for_async)
+                    // Ask again when the story reads far from the requested grade
+                    match readability::analyze(&st) {
+                        Some(r) if r.is_far_off(level.grade_level) => {
+                            let msg = format!(
+                                "AI story read like grade {} instead of {}",
+                                r.grade_label(),
+                                adaptive::grade_label(level.grade_level)
+                            );
+                            if regenerations_left > 0 {
+                                regenerations_left -= 1;
+                                notes.push(format!("{}; asked for a new one", msg));
+                                continue;
+                            }
+                            if check_reading_level {
+                                notes.push(msg);
+                            }
+                            break (st, true, result.source);
+                        }
+                        _ => break (st, true, result.source),
+                    }
                 };
+                use_ai.set(used_ai);
+                story_source.set(source);
 
-                if let Some(msg) = error_msg {
-                    error.set(Some(msg));
-                } else {
+                if notes.is_empty() {
                     error.set(None);
+                } else if used_ai {
+                    error.set(Some(format!("{}.", notes.join("; "))));
+                } else {
+                    error.set(Some(format!("{}; using fallback.", notes.join("; "))));
                 }
 
-                let qp = final_story
-                    .questions
-                    .iter()
-                    .map(|_| QuestionProgress {
-                        attempts: 0,
-                        is_correct: false,
-                        skipped: false,
-                    })
-                    .collect::<Vec<_>>();
-
+                quiz.reset(Some(&final_story));
                 story_state.set(Some(final_story));
-                question_progress.set(qp);
-                current_question.set(0);
                 phase.set(AppPhase::ReadStory);
                 is_loading.set(false);
             });
         })
     };
 
-    // Child acknowledges reading full story; switch to questions
-    let on_ack_read_story = {
-        let phase = phase.clone();
-        Callback::from(move |_| {
-            phase.set(AppPhase::Questioning);
-        })
-    };
-
-    // Handle radio choice selection
-    let on_choice_change = {
-        let selected_choice = selected_choice.clone();
-        Callback::from(move |e: Event| {
-            let input: HtmlInputElement = e.target_unchecked_into();
-            if let Ok(idx) = input.value().parse::<usize>() {
-                selected_choice.set(Some(idx));
-            }
-        })
-    };
-
-    // Check current answer (MC only), linear progression
-    let on_check_answer = {
-        let story = story.clone();
-        let question_progress = question_progress.clone();
-        let current_question = current_question.clone();
-        let selected_choice = selected_choice.clone();
+    // Give up on the story being generated and go back to topic selection
+    let on_cancel_generate = {
+        let pending_request = pending_request.clone();
         let phase = phase.clone();
+        let is_loading = is_loading.clone();
         let error = error.clone();
-
-        Callback::from(move |_| {
-            // Clear any old error first
+        Callback::from(move |_: MouseEvent| {
+            pending_request.borrow().cancel();
+            is_loading.set(false);
             error.set(None);
-
-            let Some(st) = (*story).clone() else {
-                error.set(Some("No story is loaded yet.".into()));
-                return;
-            };
-
-            let mut qp_vec = (*question_progress).clone();
-            let q_index = *current_question;
-            if q_index >= st.questions.len() {
-                return;
-            }
-
-            // If this question is already done, ignore further clicks.
-            let current_qp = &qp_vec[q_index];
-            if current_qp.is_correct || current_qp.skipped {
-                return;
-            }
-
-            let q = &st.questions[q_index];
-            let selected = (*selected_choice).clone();
-
-            // Don't count attempts if no choice is selected
-            if selected.is_none() {
-                error.set(Some("Please choose an answer before checking.".into()));
-                return;
-            }
-
-            let is_correct = is_answer_correct(q, selected);
-
-            let mut qp = qp_vec[q_index].clone();
-            qp.attempts += 1;
-
-            if is_correct {
-                qp.is_correct = true;
-                qp_vec[q_index] = qp;
-                question_progress.set(qp_vec);
-
-                // Move linearly to next question, or finish
-                let next_index = q_index + 1;
-                if next_index < st.questions.len() {
-                    current_question.set(next_index);
-                    selected_choice.set(None);
-                    phase.set(AppPhase::Questioning);
-                } else {
-                    phase.set(AppPhase::Finished);
-                }
-            } else {
-                // Mark attempt but stay on same question
-                qp_vec[q_index] = qp;
-                question_progress.set(qp_vec);
-            }
+            phase.set(AppPhase::SelectTopic);
         })
     };
 
-    // Skip question (0 points), linear progression
-    let on_skip_question = {
-        let story = story.clone();
-        let question_progress = question_progress.clone();
-        let current_question = current_question.clone();
-        let selected_choice = selected_choice.clone();
+    // Child acknowledges reading full story; switch to questions
+    let on_ack_read_story = {
         let phase = phase.clone();
-
         Callback::from(move |_| {
-            let Some(st) = (*story).clone() else {
-                return;
-            };
-
-            let mut qp_vec = (*question_progress).clone();
-            let idx = *current_question;
-            if idx >= qp_vec.len() {
-                return;
-            }
-
-            // Already done? Ignore.
-            if qp_vec[idx].is_correct || qp_vec[idx].skipped {
-                return;
-            }
-
-            let mut qp = qp_vec[idx].clone();
-            qp.skipped = true;
-            qp_vec[idx] = qp;
-            question_progress.set(qp_vec);
-
-            let next_index = idx + 1;
-            if next_index < st.questions.len() {
-                current_question.set(next_index);
-                selected_choice.set(None);
-                phase.set(AppPhase::Questioning);
-            } else {
-                phase.set(AppPhase::Finished);
-            }
+            phase.set(AppPhase::Questioning);
         })
     };
 
     // Retry the same story with fresh question progress
     let on_retry_story = {
-        let question_progress = question_progress.clone();
-        let current_question = current_question.clone();
-        let selected_choice = selected_choice.clone();
+        let story = story.clone();
+        let quiz = quiz.clone();
         let phase = phase.clone();
         let error = error.clone();
 
@@ -360,206 +1011,454 @@ fn app() -> Html {
             // Clear any old error
             error.set(None);
 
-            let old_qp = (*question_progress).clone();
-            if old_qp.is_empty() {
+            if quiz.progress.is_empty() {
                 // No questions to retry; nothing to do
                 return;
             }
 
-            // Reset attempts / correctness / skipped for each question,
-            // preserving the same length as the story's questions.
-            let reset_vec = old_qp
-                .iter()
-                .map(|_| QuestionProgress {
-                    attempts: 0,
-                    is_correct: false,
-                    skipped: false,
-                })
-                .collect::<Vec<_>>();
-
-            question_progress.set(reset_vec);
-            current_question.set(0);
-            selected_choice.set(None);
+            quiz.reset(story.as_ref());
             phase.set(AppPhase::Questioning);
         })
     };
 
-    // Start over from topic selection
+    // Start over from topic selection (or, while a story is locked, from
+    // the top of that story)
     let on_restart = {
         let topic = topic.clone();
         let num_paragraphs = num_paragraphs.clone();
+        let locked_story = locked_story.clone();
         let story = story.clone();
-        let question_progress = question_progress.clone();
-        let current_question = current_question.clone();
+        let quiz = quiz.clone();
         let phase = phase.clone();
         let use_ai = use_ai.clone();
-        let selected_choice = selected_choice.clone();
         let error = error.clone();
 
         Callback::from(move |_| {
             topic.set("".into());
             num_paragraphs.set(3);
-            story.set(None);
-            question_progress.set(Vec::new());
-            current_question.set(0);
-            phase.set(AppPhase::SelectTopic);
+            quiz.reset(locked_story.as_ref());
+            story.set((*locked_story).clone());
+            if locked_story.is_some() {
+                phase.set(AppPhase::ReadStory);
+            } else {
+                phase.set(AppPhase::SelectTopic);
+            }
             use_ai.set(false);
-            selected_choice.set(None);
             error.set(None);
         })
     };
 
-    // Compute score and letter grade
+    // Compute score (per the chosen scoring policy) and grade band
     let (score_percent, grade_label) = {
         let st_opt = (*story).clone();
-        let qp_vec = (*question_progress).clone();
+        let qp_vec = (*quiz.progress).clone();
         if let Some(st) = st_opt {
             if st.questions.is_empty() || qp_vec.is_empty() {
                 (None, None)
             } else {
-                let total = st.questions.len() as f32;
-                let points_per = 100.0 / total;
-
-                let mut score = 0.0;
-                for qprog in qp_vec.iter() {
-                    if qprog.skipped {
-                        continue;
-                    }
-                    if qprog.is_correct {
-                        score += points_per;
-                    }
-                }
-
-                let score_rounded = score.round() as i32;
-                let grade = if score_rounded >= 90 {
-                    ("A".to_string(), "Excellent".to_string())
-                } else if score_rounded >= 80 {
-                    ("B".to_string(), "Good".to_string())
-                } else if score_rounded >= 70 {
-                    ("C".to_string(), "Needs Practice".to_string())
-                } else {
-                    ("Unsatisfactory".to_string(), "Keep Working!".to_string())
-                };
-                (Some(score_rounded), Some(grade))
+                let score_rounded = scoring::score_percent(&qp_vec, settings.scoring).unwrap_or(0);
+                let grade = settings.grading.grade(score_rounded).cloned();
+                (Some(score_rounded), grade)
             }
         } else {
             (None, None)
         }
     };
 
+    // Question the story box should point at (evidence sentence, vocab word)
+    let focus_question = match (&*phase, &*story) {
+        (AppPhase::Questioning, Some(st)) => st.questions.get(*quiz.current_question).cloned(),
+        _ => None,
+    };
+    let evidence_locked = quiz.current_done();
+
     html! {
         <div class="app-shell">
             <header>
-                <h1>{"Kids Story Trainer (5th Grade)"}</h1>
+                <h1>{"Kids Story Trainer (Grade "}{ adaptive::grade_label(next_level.grade_level) }{")"}</h1>
                 <p class="sub">
-                    {"Pick a topic, let the app (or Cloudflare AI) write a story, then practice comprehension with multiple-choice questions."}
+                    {"Pick a topic, let the app (or Cloudflare AI) write a story, then practice comprehension with multiple-choice, true/false, short-answer, find-the-sentence, put-in-order and vocabulary questions."}
                 </p>
                 <div>
-                    <span class="pill tag-ai">{"Cloudflare Worker AI (primary)"}</span>
+                    if active_profile.endpoint.is_some() {
+                        <span class="pill tag-ai">{ active_profile.display_name() }{" (primary)"}</span>
+                    }
                     <span class="pill tag-fallback">{"Built-in stories (fallback)"}</span>
                 </div>
+                if *phase != AppPhase::Dashboard {
+                    <button class="btn btn-secondary" onclick={on_open_dashboard}>
+                        {"📈 My progress"}
+                    </button>
+                }
             </header>
 
             <main>
-                <section>
-                    <h2>{"1. Choose a topic & story size"}</h2>
-                    <div class="row">
-                        <div>
-                            <label>{"Story topic (kid friendly)"}</label>
-                            <input
-                                type="text"
-                                placeholder="Volcano safety, a school field trip, a science fair, a lost puppy..."
-                                value={(*topic).clone()}
-                                oninput={on_topic_input}
-                            />
-                        </div>
-                        <div style="max-width: 200px;">
-                            <label>{"Number of paragraphs"}</label>
-                            <input
-                                type="number"
-                                min="1"
-                                max="6"
-                                value={num_paragraphs.to_string()}
-                                oninput={on_paragraphs_input}
-                            />
-                            <p class="sub">{"Usually 2–5 works well for 5th grade."}</p>
-                        </div>
-                    </div>
-                    <button class="btn btn-primary" onclick={on_generate_story} disabled={*is_loading}>
-                        { if *is_loading { "Generating story..." } else { "Generate Story & Questions" } }
-                    </button>
-                    if let Some(err) = &*error {
-                        <div class="error">
-                            {err}
+                if *phase == AppPhase::Dashboard {
+                    { render_dashboard_ui(&history, &blocked_topics, &on_close_dashboard) }
+                } else if *phase == AppPhase::Editor {
+                    <StoryEditor
+                        on_save_pack={on_save_editor_pack}
+                        on_close={on_close_editor}
+                        story={(*editor_story).clone()}
+                        on_use_story={editor_story.is_some().then_some(on_use_edited_story)}
+                        locked={locked_story.is_some()}
+                    />
+                } else {
+                    <section>
+                        <h2>{"1. Choose a topic, grade & story size"}</h2>
+                        <div class="row">
+                            <div>
+                                <label>{"Story topic (kid friendly)"}</label>
+                                <input
+                                    type="text"
+                                    placeholder="Volcano safety, a school field trip, a science fair, a lost puppy..."
+                                    value={(*topic).clone()}
+                                    oninput={on_topic_input}
+                                />
+                            </div>
+                            <div style="max-width: 200px;">
+                                <label>{"Number of paragraphs"}</label>
+                                <input
+                                    type="number"
+                                    min="1"
+                                    max="6"
+                                    value={next_level.num_paragraphs.to_string()}
+                                    oninput={on_paragraphs_input}
+                                    disabled={settings.adaptive_difficulty}
+                                />
+                                <p class="sub">{"Usually 2–5 works well."}</p>
+                            </div>
+                            <div style="max-width: 160px;">
+                                <label>{"Grade level"}</label>
+                                <select onchange={on_grade_change} disabled={settings.adaptive_difficulty}>
+                                    {
+                                        for (0..=adaptive::MAX_GRADE).map(|g| html! {
+                                            <option value={g.to_string()} selected={g == next_level.grade_level}>
+                                                { if g == 0 { "Kindergarten".to_string() } else { format!("Grade {}", g) } }
+                                            </option>
+                                        })
+                                    }
+                                </select>
+                            </div>
+                            <div style="max-width: 160px;">
+                                <label>{"Number of questions"}</label>
+                                <input
+                                    type="number"
+                                    min="1"
+                                    max="10"
+                                    value={num_questions.to_string()}
+                                    oninput={on_questions_input}
+                                />
+                            </div>
                         </div>
-                    }
-                </section>
-
-                {
-                    match &*phase {
-                        AppPhase::SelectTopic | AppPhase::LoadingStory => html! {},
-                        _ => {
-                            if let Some(st) = &*story {
-                                html! {
-                                    <>
-                                        <h2>{"2. Read the story"}</h2>
-                                        <div class="story-box">
-                                            <h3>{ &st.title }</h3>
+                        if settings.adaptive_difficulty {
+                            <p class="adaptive-note">
+                                {"🎯 Next story: Grade "}{ adaptive::grade_label(next_level.grade_level) }
+                                {" · "}{ next_level.num_paragraphs }{" paragraphs · "}
+                                { next_level.question_difficulty.key() }{" questions"}
+                                <br />
+                                <span class="sub">{ &recommendation.reason }</span>
+                            </p>
+                        }
+                        <details class="settings">
+                            <summary>{"Settings (for grown-ups)"}</summary>
+                            <div class="row">
+                                <div style="max-width: 320px;">
+                                    <label>{"Story source"}</label>
+                                    <select onchange={on_backend_change}>
+                                        {
+                                            for app_config.profiles.iter().map(|p| html! {
+                                                <option value={p.name.clone()} selected={p.name == *backend_name}>
+                                                    { p.display_name() }
+                                                </option>
+                                            })
+                                        }
+                                    </select>
+                                </div>
+                                <div style="max-width: 320px;">
+                                    <label>{"Scoring"}</label>
+                                    <select onchange={on_scoring_change}>
+                                        {
+                                            for ScoringPolicy::ALL.iter().map(|policy| html! {
+                                                <option
+                                                    value={policy.key()}
+                                                    selected={*policy == settings.scoring}
+                                                >
+                                                    { policy.label() }
+                                                </option>
+                                            })
+                                        }
+                                    </select>
+                                </div>
+                                <div style="max-width: 320px;">
+                                    <label>{"Grading scale"}</label>
+                                    <select onchange={on_grading_change}>
+                                        {
+                                            for GradingScale::PRESETS.iter().map(|kind| html! {
+                                                <option
+                                                    value={kind.key()}
+                                                    selected={*kind == settings.grading.kind}
+                                                >
+                                                    { kind.label() }
+                                                </option>
+                                            })
+                                        }
+                                    </select>
+                                </div>
+                            </div>
+                            <label class="choice">
+                                <input
+                                    type="checkbox"
+                                    checked={settings.save_history}
+                                    onchange={on_save_history_toggle}
+                                />
+                                {"Save reading history on this device (never sent anywhere)"}
+                            </label>
+                            <label class="choice">
+                                <input
+                                    type="checkbox"
+                                    checked={settings.adaptive_difficulty}
+                                    onchange={on_adaptive_toggle}
+                                />
+                                {"Adaptive difficulty (recent scores pick the next grade level and length)"}
+                            </label>
+                            <label class="choice">
+                                <input
+                                    type="checkbox"
+                                    checked={settings.check_reading_level}
+                                    onchange={on_reading_level_toggle}
+                                />
+                                {"Ask the AI for a new story when one reads far from the chosen grade"}
+                            </label>
+                            <label class="choice">
+                                <input
+                                    type="checkbox"
+                                    checked={narration.is_some()}
+                                    onchange={on_narration_toggle}
+                                    disabled={!speech::is_supported()}
+                                />
+                                {"🔊 Read-aloud buttons for this session only (leave off for reading assessments)"}
+                            </label>
+                            if let Some(n) = &*narration {
+                                <div class="row">
+                                    <div style="max-width: 320px;">
+                                        <label>{"Voice"}</label>
+                                        <select onchange={on_voice_change}>
+                                            <option value="" selected={n.voice.is_none()}>{"Browser default"}</option>
                                             {
-                                                for st.paragraphs.iter().enumerate().map(|(i, p)| {
-                                                    html! {
-                                                        <div class="paragraph">
-                                                            <strong>{"Paragraph "}{ i + 1 }{":"}</strong>
-                                                            <br />
-                                                            { p }
-                                                        </div>
-                                                    }
+                                                for voices.iter().map(|v| html! {
+                                                    <option
+                                                        value={v.uri.clone()}
+                                                        selected={n.voice.as_deref() == Some(v.uri.as_str())}
+                                                    >
+                                                        { &v.label }
+                                                    </option>
                                                 })
                                             }
-                                        </div>
-                                        <button class="btn btn-secondary" onclick={reset_quiz_state.clone()}>
-                                            {"Back to this story"}
-                                        </button>
-                                        <button class="btn btn-primary" onclick={on_ack_read_story.clone()}>
-                                            {"I read the story – start questions"}
-                                        </button>
-                                    </>
+                                        </select>
+                                    </div>
+                                    <div style="max-width: 320px;">
+                                        <label>{"Reading speed"}</label>
+                                        <select onchange={on_rate_change}>
+                                            {
+                                                for speech::RATES.iter().map(|(rate, label)| html! {
+                                                    <option value={rate.to_string()} selected={*rate == n.rate}>
+                                                        { *label }
+                                                    </option>
+                                                })
+                                            }
+                                        </select>
+                                    </div>
+                                </div>
+                            }
+                            <button class="btn btn-small" onclick={on_toggle_word_lists}>
+                                { if *show_word_lists { "Hide word lists" } else { "Show word lists (grown-ups only)" } }
+                            </button>
+                            if *show_word_lists {
+                                <div class="row">
+                                    <div style="max-width: 320px;">
+                                        <label>{"Blocked topic words (one per line)"}</label>
+                                        <textarea
+                                            rows="4"
+                                            value={settings.topic_filter.blocked.join("\n")}
+                                            onchange={on_blocked_list_change}
+                                        />
+                                    </div>
+                                    <div style="max-width: 320px;">
+                                        <label>{"Always allowed phrases (e.g. killer whale)"}</label>
+                                        <textarea
+                                            rows="4"
+                                            value={settings.topic_filter.allowed.join("\n")}
+                                            onchange={on_allowed_list_change}
+                                        />
+                                    </div>
+                                </div>
+                                <button class="btn btn-small" onclick={on_reset_topic_lists}>
+                                    {"Reset topic lists"}
+                                </button>
+                            }
+                            <div class="row">
+                                <div style="max-width: 320px;">
+                                    <label>{"If an AI story has unsuitable words"}</label>
+                                    <select onchange={on_screening_action_change}>
+                                        {
+                                            for ScreeningAction::ALL.iter().map(|action| html! {
+                                                <option
+                                                    value={action.key()}
+                                                    selected={*action == settings.content_rules.action}
+                                                >
+                                                    { action.label() }
+                                                </option>
+                                            })
+                                        }
+                                    </select>
+                                </div>
+                                if *show_word_lists {
+                                    <div style="max-width: 320px;">
+                                        <label>{"Extra words to keep out of stories"}</label>
+                                        <textarea
+                                            rows="3"
+                                            value={settings.content_rules.custom.join("\n")}
+                                            onchange={on_custom_words_change}
+                                        />
+                                    </div>
+                                }
+                            </div>
+                            <div class="packs">
+                                <label>{"Story packs (.json or .toml)"}</label>
+                                <input type="file" accept=".json,.toml" onchange={on_import_pack} />
+                                <button class="btn btn-small" onclick={on_open_editor}>
+                                    {"✏️ Write a story pack"}
+                                </button>
+                                {
+                                    for library.iter().enumerate().map(|(i, p)| {
+                                        let on_remove_pack = on_remove_pack.clone();
+                                        html! {
+                                            <div class="pack-row">
+                                                <span>
+                                                    <strong>{ &p.name }</strong>
+                                                    { format!(" · {} stories", p.story_count()) }
+                                                    if !p.author.is_empty() {
+                                                        { format!(" · by {}", p.author) }
+                                                    }
+                                                    if !p.license.is_empty() {
+                                                        { format!(" · {}", p.license) }
+                                                    }
+                                                </span>
+                                                <button class="btn btn-small" onclick={Callback::from(move |_: MouseEvent| on_remove_pack.emit(i))}>
+                                                    {"Remove"}
+                                                </button>
+                                            </div>
+                                        }
+                                    })
+                                }
+                            </div>
+                            if let Some(locked) = &*locked_story {
+                                <div class="pack-row">
+                                    <span>{ format!("🔒 Locked story: \"{}\"", locked.title) }</span>
+                                    <HoldButton label="Hold to unlock (grown-ups)" on_confirm={on_unlock_story} />
+                                </div>
+                            }
+                            <p class="sub">
+                                { format!("{} finished stories in history.", history.len()) }
+                                {" Story packs and a locked story are teacher content: they are kept on this device either way and removed above."}
+                            </p>
+                            <button class="btn btn-secondary" onclick={on_clear_data}>
+                                {"Clear reading history and settings"}
+                            </button>
+                        </details>
+                        if locked_story.is_some() {
+                            <p class="adaptive-note">
+                                {"🔒 A grown-up picked this story. Read it again or try the questions once more!"}
+                            </p>
+                        }
+                        <button
+                            class="btn btn-primary"
+                            onclick={on_generate_story}
+                            disabled={*is_loading || locked_story.is_some()}
+                        >
+                            { if *is_loading { "Generating story..." } else { "Generate Story & Questions" } }
+                        </button>
+                        if *is_loading {
+                            <button class="btn btn-secondary" onclick={on_cancel_generate}>
+                                {"Cancel"}
+                            </button>
+                        }
+                        if let Some(err) = &*error {
+                            <div class="error">
+                                {err}
+                            </div>
+                        }
+                    </section>
+
+                    {
+                        match &*phase {
+                            AppPhase::SelectTopic => html! {},
+                            _ => {
+                                if let Some(st) = &*story {
+                                    html! {
+                                        <>
+                                            <h2>{"2. Read the story"}</h2>
+                                            { render_story_box(
+                                                st,
+                                                focus_question.as_ref(),
+                                                *quiz.selected_choice,
+                                                evidence_locked,
+                                                &quiz.on_sentence_click,
+                                                (*narration).as_ref(),
+                                            ) }
+                                            if *phase == AppPhase::LoadingStory {
+                                                // Streaming: the story is still arriving
+                                                <button class="btn btn-primary" disabled={true}>
+                                                    {"Questions are on the way..."}
+                                                </button>
+                                            } else {
+                                                <button class="btn btn-secondary" onclick={reset_quiz_state.clone()}>
+                                                    {"Back to this story"}
+                                                </button>
+                                                <button class="btn btn-primary" onclick={on_ack_read_story.clone()}>
+                                                    {"I read the story – start questions"}
+                                                </button>
+                                                if *phase == AppPhase::ReadStory {
+                                                    <HoldButton
+                                                        label="✏️ Hold to edit this story (grown-ups)"
+                                                        on_confirm={on_edit_current_story.clone()}
+                                                    />
+                                                }
+                                            }
+                                        </>
+                                    }
+                                } else {
+                                    html! {}
                                 }
-                            } else {
-                                html! {}
                             }
                         }
                     }
-                }
 
-                {
-                    match &*phase {
-                        AppPhase::Questioning => render_question_ui(
-                            &story,
-                            &question_progress,
-                            &current_question,
-                            &selected_choice,
-                            &on_choice_change,
-                            &on_check_answer,
-                            &on_skip_question,
-                        ),
-                        AppPhase::Finished => render_results_ui(
-                            &story,
-                            &question_progress,
-                            &score_percent,
-                            &grade_label,
-                            &on_restart,
-                            &on_retry_story,
-                        ),
-                        _ => html! {},
+                    {
+                        match &*phase {
+                            AppPhase::Questioning => {
+                                render_question_ui(&story, &quiz, (*narration).as_ref())
+                            }
+                            AppPhase::Finished => render_results_ui(
+                                &story,
+                                &quiz.progress,
+                                &score_percent,
+                                &grade_label,
+                                &settings,
+                                &on_restart,
+                                &on_retry_story,
+                            ),
+                            _ => html! {},
+                        }
                     }
                 }
             </main>
 
             <footer class="footer">
                 <span>
-                    {"v0.7.2 – Rust + Yew + WASM · MC-only"}
-                    { if *use_ai { " · Cloudflare AI" } else { " · Fallback story" } }
+                    {"v0.9.0 – Rust + Yew + WASM · mixed question types"}
+                    { if *use_ai { format!(" · {}", *story_source) } else { " · Fallback story".to_string() } }
                 </span>
             </footer>
         </div>
@@ -568,19 +1467,127 @@ fn app() -> Html {
 
 // --- Helper rendering functions -------------------------------------------------
 
+// The story passage. While `question` is an evidence question its paragraph
+// becomes tappable sentences; a vocabulary question highlights its word.
+// With `narration` each paragraph gets a read-aloud button.
+fn render_story_box(
+    st: &Story,
+    question: Option<&Question>,
+    selected_choice: Option<usize>,
+    evidence_locked: bool,
+    on_sentence_click: &Callback<usize>,
+    narration: Option<&speech::Narration>,
+) -> Html {
+    let evidence_paragraph = question.and_then(|q| match q.kind {
+        QuestionKind::EvidenceSelect { .. } => Some(q.paragraph_index),
+        _ => None,
+    });
+    let vocab_focus = question.and_then(|q| match &q.kind {
+        QuestionKind::Vocabulary { word, .. } => Some((q.paragraph_index, word.clone())),
+        _ => None,
+    });
+
+    html! {
+        <div class="story-box">
+            <h3>
+                { &st.title }
+                if let Some(r) = readability::analyze(st) {
+                    <span class="pill reading-level" title={r.summary()}>
+                        { format!("Reads like grade {} · ~{}L", r.grade_label(), r.lexile_estimate()) }
+                    </span>
+                }
+            </h3>
+            if narration.is_some() {
+                <div class="narration-controls">
+                    { read_aloud_button(
+                        format!("{}. {}", st.title, st.paragraphs.join(" ")),
+                        "🔊 Read the whole story",
+                        narration,
+                    ) }
+                    <button class="btn btn-secondary btn-small" onclick={Callback::from(|_: MouseEvent| speech::pause())}>
+                        {"⏸ Pause"}
+                    </button>
+                    <button class="btn btn-secondary btn-small" onclick={Callback::from(|_: MouseEvent| speech::resume())}>
+                        {"▶ Resume"}
+                    </button>
+                    <button class="btn btn-secondary btn-small" onclick={Callback::from(|_: MouseEvent| speech::stop())}>
+                        {"⏹ Stop"}
+                    </button>
+                </div>
+            }
+            {
+                for st.paragraphs.iter().enumerate().map(|(i, p)| {
+                    if evidence_paragraph == Some(i) {
+                        html! {
+                            <div class="paragraph highlight">
+                                <strong>{"Paragraph "}{ i + 1 }{":"}</strong>
+                                { read_aloud_button(p.clone(), "🔊", narration) }
+                                <br />
+                                {
+                                    for split_sentences(p).into_iter().enumerate().map(|(si, sentence)| {
+                                        let is_selected = selected_choice == Some(si);
+                                        let onclick = if evidence_locked {
+                                            None
+                                        } else {
+                                            let on_sentence_click = on_sentence_click.clone();
+                                            Some(Callback::from(move |_: MouseEvent| on_sentence_click.emit(si)))
+                                        };
+                                        html! {
+                                            <span class={classes!("sentence", is_selected.then_some("selected"))} {onclick}>
+                                                { sentence }{" "}
+                                            </span>
+                                        }
+                                    })
+                                }
+                            </div>
+                        }
+                    } else if let Some((_, word)) = vocab_focus.as_ref().filter(|(vi, _)| *vi == i) {
+                        html! {
+                            <div class="paragraph highlight">
+                                <strong>{"Paragraph "}{ i + 1 }{":"}</strong>
+                                { read_aloud_button(p.clone(), "🔊", narration) }
+                                <br />
+                                { highlight_word(p, word) }
+                            </div>
+                        }
+                    } else {
+                        html! {
+                            <div class="paragraph">
+                                <strong>{"Paragraph "}{ i + 1 }{":"}</strong>
+                                { read_aloud_button(p.clone(), "🔊", narration) }
+                                <br />
+                                { p }
+                            </div>
+                        }
+                    }
+                })
+            }
+        </div>
+    }
+}
+
 fn render_question_ui(
     story: &UseStateHandle<Option<Story>>,
-    question_progress: &UseStateHandle<Vec<QuestionProgress>>,
-    current_question: &UseStateHandle<usize>,
-    selected_choice: &UseStateHandle<Option<usize>>,
-    on_choice_change: &Callback<Event>,
-    on_check_answer: &Callback<MouseEvent>,
-    on_skip_question: &Callback<MouseEvent>,
+    quiz: &Quiz,
+    narration: Option<&speech::Narration>,
 ) -> Html {
     let Some(st) = (**story).clone() else {
         return html! {};
     };
-    let qp_vec = &**question_progress;
+    let Quiz {
+        progress,
+        current_question,
+        selected_choice,
+        typed_answer,
+        sequence_order,
+        on_choice_change,
+        on_sentence_click: _,
+        on_move_event,
+        on_answer_input,
+        on_check_answer,
+        on_skip_question,
+    } = quiz;
+    let qp_vec = &**progress;
     if st.questions.is_empty() || qp_vec.is_empty() {
         return html! {};
     }
@@ -597,20 +1604,26 @@ fn render_question_ui(
 
     let is_done = qp.is_correct || qp.skipped;
 
-    // For display: clamp weird values so "1 attempt" displays as 1 when correct
-    let display_attempts = if qp.is_correct && qp.attempts > 1 {
-        1
+    // Status message: simple + truthful
+    let status_msg = if qp.is_correct {
+        " · ✅ Correct!".to_string()
+    } else if qp.skipped && qp.credit > 0.0 {
+        format!(" · Moved on with partial credit ({}%).", (qp.credit * 100.0).round())
+    } else if qp.skipped {
+        " · This question was skipped (0 points).".to_string()
+    } else if qp.credit > 0.0 {
+        format!(
+            " · 🧩 Partly right – best so far {}%. Try again or move on.",
+            (qp.credit * 100.0).round()
+        )
     } else {
-        qp.attempts
+        String::new()
     };
 
-    // Status message: simple + truthful
-    let status_msg: &str = if qp.is_correct {
-        " · ✅ Correct!"
-    } else if qp.skipped {
-        " · This question was skipped (0 points)."
+    let skip_label = if qp.credit > 0.0 {
+        "Move on (keep partial credit)"
     } else {
-        ""
+        "Skip (0 pts)"
     };
 
     html! {
@@ -622,7 +1635,10 @@ fn render_question_ui(
                     {" · "}
                     {"Completed: "}{ answered }{"/"}{ total }
                 </p>
-                <p><strong>{"Q: "}{ &q.text }</strong></p>
+                <p>
+                    <strong>{"Q: "}{ &q.text }</strong>
+                    { read_aloud_button(read_aloud_text(q, sequence_order), "🔊", narration) }
+                </p>
 
                 {
                     match &q.kind {
@@ -651,6 +1667,158 @@ fn render_question_ui(
                                 </div>
                             }
                         }
+                        QuestionKind::Vocabulary { word, choices, .. } => {
+                            html! {
+                                <div class="choices">
+                                    <p class="sub">
+                                        {"Find "}<mark class="vocab-word">{ word }</mark>
+                                        {" in Paragraph "}{ q.paragraph_index + 1 }
+                                        {" and reread the sentence around it."}
+                                    </p>
+                                    {
+                                        for choices.iter().enumerate().map(|(i, choice)| {
+                                            let checked = (*selected_choice).map(|v| v == i).unwrap_or(false);
+                                            html! {
+                                                <label class="choice">
+                                                    <input
+                                                        type="radio"
+                                                        name="vocab-choice"
+                                                        value={i.to_string()}
+                                                        checked={checked}
+                                                        onchange={on_choice_change.clone()}
+                                                        disabled={is_done}
+                                                    />
+                                                    { choice }
+                                                </label>
+                                            }
+                                        })
+                                    }
+                                </div>
+                            }
+                        }
+                        QuestionKind::TrueFalse { .. } => {
+                            html! {
+                                <div class="choices">
+                                    {
+                                        for ["True", "False"].iter().enumerate().map(|(i, label)| {
+                                            let checked = (*selected_choice).map(|v| v == i).unwrap_or(false);
+                                            html! {
+                                                <label class="choice">
+                                                    <input
+                                                        type="radio"
+                                                        name="tf-choice"
+                                                        value={i.to_string()}
+                                                        checked={checked}
+                                                        onchange={on_choice_change.clone()}
+                                                        disabled={is_done}
+                                                    />
+                                                    { *label }
+                                                </label>
+                                            }
+                                        })
+                                    }
+                                </div>
+                            }
+                        }
+                        QuestionKind::EvidenceSelect { .. } => {
+                            let sentences = st
+                                .paragraphs
+                                .get(q.paragraph_index)
+                                .map(|p| split_sentences(p))
+                                .unwrap_or_default();
+                            let picked = (*selected_choice).and_then(|i| sentences.get(i).cloned());
+                            html! {
+                                <div class="evidence">
+                                    <p class="sub">
+                                        {"Tap the sentence in the highlighted paragraph (Paragraph "}
+                                        { q.paragraph_index + 1 }
+                                        {") that proves your answer."}
+                                    </p>
+                                    {
+                                        if let Some(sentence) = picked {
+                                            html! { <p>{"You picked: "}<em>{ sentence }</em></p> }
+                                        } else {
+                                            html! {}
+                                        }
+                                    }
+                                </div>
+                            }
+                        }
+                        QuestionKind::Sequence { events, .. } => {
+                            let order = current_sequence_order(q, sequence_order);
+                            let last = order.len().saturating_sub(1);
+                            html! {
+                                <div class="sequence">
+                                    <p class="sub">
+                                        {"Put the events in order from first to last. Use the arrows or drag an event to move it."}
+                                    </p>
+                                    <ol>
+                                        {
+                                            for order.iter().enumerate().map(|(pos, &event_idx)| {
+                                                let move_to = |to: usize| {
+                                                    let on_move_event = on_move_event.clone();
+                                                    Callback::from(move |_: MouseEvent| on_move_event.emit((pos, to)))
+                                                };
+                                                let ondragstart = {
+                                                    let selected_choice = selected_choice.clone();
+                                                    Callback::from(move |_: DragEvent| selected_choice.set(Some(pos)))
+                                                };
+                                                let ondragover = Callback::from(|e: DragEvent| e.prevent_default());
+                                                let ondrop = {
+                                                    let selected_choice = selected_choice.clone();
+                                                    let on_move_event = on_move_event.clone();
+                                                    Callback::from(move |e: DragEvent| {
+                                                        e.prevent_default();
+                                                        if let Some(from) = *selected_choice {
+                                                            on_move_event.emit((from, pos));
+                                                        }
+                                                        selected_choice.set(None);
+                                                    })
+                                                };
+                                                html! {
+                                                    <li
+                                                        class="sequence-item"
+                                                        draggable={(!is_done).to_string()}
+                                                        {ondragstart}
+                                                        {ondragover}
+                                                        {ondrop}
+                                                    >
+                                                        <button
+                                                            class="btn btn-secondary btn-small"
+                                                            onclick={move_to(pos.saturating_sub(1))}
+                                                            disabled={is_done || pos == 0}
+                                                        >
+                                                            {"▲"}
+                                                        </button>
+                                                        <button
+                                                            class="btn btn-secondary btn-small"
+                                                            onclick={move_to((pos + 1).min(last))}
+                                                            disabled={is_done || pos == last}
+                                                        >
+                                                            {"▼"}
+                                                        </button>
+                                                        { events.get(event_idx).cloned().unwrap_or_default() }
+                                                    </li>
+                                                }
+                                            })
+                                        }
+                                    </ol>
+                                </div>
+                            }
+                        }
+                        QuestionKind::ShortAnswer { .. } => {
+                            html! {
+                                <div class="short-answer">
+                                    <input
+                                        type="text"
+                                        placeholder="Type your answer..."
+                                        value={(**typed_answer).clone()}
+                                        oninput={on_answer_input.clone()}
+                                        disabled={is_done}
+                                    />
+                                </div>
+                            }
+                        }
                     }
                 }
 
@@ -659,12 +1827,12 @@ fn render_question_ui(
                         {"Check Answer"}
                     </button>
                     <button class="btn btn-secondary" onclick={on_skip_question.clone()} disabled={is_done}>
-                        {"Skip (0 pts)"}
+                        { skip_label }
                     </button>
                 </div>
 
                 <div class="status-line">
-                    <strong>{"Attempts: "}{ display_attempts }</strong>
+                    <strong>{"Attempts: "}{ qp.attempts }</strong>
                     { status_msg }
                 </div>
             </div>
@@ -676,7 +1844,8 @@ fn render_results_ui(
     story: &UseStateHandle<Option<Story>>,
     question_progress: &UseStateHandle<Vec<QuestionProgress>>,
     score_percent: &Option<i32>,
-    grade_label: &Option<(String, String)>,
+    grade_label: &Option<GradeBand>,
+    settings: &AppSettings,
     on_restart: &Callback<MouseEvent>,
     on_retry_story: &Callback<MouseEvent>,
 ) -> Html {
@@ -685,16 +1854,15 @@ fn render_results_ui(
     };
     let qp_vec = &**question_progress;
 
-    let (grade_str, grade_desc, grade_class) = if let (Some(score), Some((grade, desc))) =
-        (score_percent.clone(), grade_label.clone())
+    let (grade_str, grade_desc, grade_class) = if let (Some(score), Some(band)) =
+        (*score_percent, grade_label.clone())
     {
-        let badge_class = match grade.as_str() {
-            "A" => "badge badge-a",
-            "B" => "badge badge-b",
-            "C" => "badge badge-c",
-            _ => "badge badge-u",
+        let grade_str = if settings.grading.show_percent {
+            format!("{} ({}%)", band.label, score)
+        } else {
+            band.label
         };
-        (format!("{} ({}%)", grade, score), desc, badge_class.to_string())
+        (grade_str, band.description, band.tone.badge_class().to_string())
     } else {
         (
             "No score".to_string(),
@@ -703,6 +1871,8 @@ fn render_results_ui(
         )
     };
 
+    let accuracy = Accuracy::from_progress(qp_vec);
+
     // Allow retry for any completed quiz with score < 100
     let allow_retry = if let Some(score) = score_percent {
         *score < 100
@@ -720,29 +1890,33 @@ fn render_results_ui(
                     <span class={grade_class}>{ grade_str }</span>
                     {" · "}{ grade_desc }
                 </p>
+                <p class="sub">
+                    {"Scoring: "}{ settings.scoring.label() }
+                </p>
+                <p>
+                    {"Accuracy: "}<strong>{ accuracy.percent() }{"%"}</strong>
+                    {" ("}{ accuracy.correct }{"/"}{ accuracy.total }{" correct)"}
+                    {" · First-try accuracy: "}<strong>{ accuracy.first_try_percent() }{"%"}</strong>
+                    {" ("}{ accuracy.first_try_correct }{"/"}{ accuracy.total }{")"}
+                </p>
                 <ul>
                     {
                         for st.questions.iter().enumerate().map(|(i, _q)| {
                             let qp = &qp_vec[i];
 
-                            // UI guard: if it's correct but attempts somehow >1, show 1
-                            let display_attempts = if qp.is_correct && qp.attempts > 1 {
-                                1
-                            } else {
-                                qp.attempts
-                            };
-
-                            let status = if qp.skipped {
-                                "Skipped (0 pts)"
+                            let status = if qp.skipped && qp.credit > 0.0 {
+                                format!("Partial credit ({}%)", (qp.credit * 100.0).round())
+                            } else if qp.skipped {
+                                "Skipped (0 pts)".to_string()
                             } else if qp.is_correct {
-                                "Correct"
+                                "Correct".to_string()
                             } else {
-                                "Incomplete"
+                                "Incomplete".to_string()
                             };
                             html! {
                                 <li>
                                     {"Q"}{ i + 1 }{": "}{ status }
-                                    {" · attempts: "}{ display_attempts }
+                                    {" · attempts: "}{ qp.attempts }
                                 </li>
                             }
                         })
@@ -769,284 +1943,634 @@ fn render_results_ui(
     }
 }
 
+fn render_dashboard_ui(
+    history: &[SessionRecord],
+    blocked_topics: &[BlockedTopic],
+    on_close: &Callback<MouseEvent>,
+) -> Html {
+    let summary = progress::summarize(history);
+    let weak = summary.weak_kinds();
+    let percent_or_dash = |v: Option<i32>| v.map(|p| format!("{}%", p)).unwrap_or_else(|| "–".into());
+
+    html! {
+        <section>
+            <h2>{"📈 Reading progress"}</h2>
+            {
+                if summary.stories_read == 0 {
+                    html! {
+                        <div class="question-box">
+                            <p>{"No finished stories yet. Finish a story quiz and it will show up here."}</p>
+                            <p class="sub">
+                                {"To keep progress after the page is closed, turn on “Save reading history on this device” in Settings."}
+                            </p>
+                        </div>
+                    }
+                } else {
+                    html! {
+                        <>
+                            <div class="question-box">
+                                <p>
+                                    {"Stories read: "}<strong>{ summary.stories_read }</strong>
+                                    {" · Average score: "}<strong>{ percent_or_dash(summary.average_score) }</strong>
+                                    {" · First-try accuracy: "}<strong>{ percent_or_dash(summary.first_try_percent) }</strong>
+                                </p>
+                            </div>
+
+                            <div class="question-box">
+                                <h3>{"First-try accuracy over time"}</h3>
+                                {
+                                    for summary.sessions.iter().rev().take(10).map(|s| html! {
+                                        <div class="progress-row">
+                                            <span class="progress-label">
+                                                { format_date(s.completed_at) }{" · "}{ &s.title }
+                                            </span>
+                                            <span class="progress-bar">
+                                                <span class="progress-fill" style={format!("width: {}%;", s.first_try_percent)}></span>
+                                            </span>
+                                            <span class="progress-value">{ s.first_try_percent }{"%"}</span>
+                                        </div>
+                                    })
+                                }
+                                <p class="sub">{"Most recent 10 stories, newest first."}</p>
+                            </div>
+
+                            <div class="question-box">
+                                <h3>{"By question type"}</h3>
+                                <ul>
+                                    {
+                                        for summary.kinds.iter().map(|k| html! {
+                                            <li>
+                                                { k.label }{": "}{ k.first_try_correct }{"/"}{ k.questions }
+                                                {" right on the first try ("}{ k.first_try_percent() }{"%)"}
+                                            </li>
+                                        })
+                                    }
+                                </ul>
+                                {
+                                    if weak.is_empty() {
+                                        html! { <p class="sub">{"No weak spots yet – nice work!"}</p> }
+                                    } else {
+                                        html! {
+                                            <p>
+                                                {"Needs practice: "}
+                                                <strong>{ weak.iter().map(|k| k.label).collect::<Vec<_>>().join(", ") }</strong>
+                                            </p>
+                                        }
+                                    }
+                                }
+                            </div>
+                        </>
+                    }
+                }
+            }
+            if !blocked_topics.is_empty() {
+                <div class="question-box">
+                    <h3>{"Blocked topics"}</h3>
+                    <ul>
+                        {
+                            for blocked_topics.iter().rev().map(|b| html! {
+                                <li>
+                                    { format_date(b.at) }{" · “"}{ &b.topic }{"” (matched “"}{ &b.matched }{"”)"}
+                                </li>
+                            })
+                        }
+                    </ul>
+                    <p class="sub">{"Topics the safety filter stopped before any story was requested, newest first."}</p>
+                </div>
+            }
+            <button class="btn btn-primary" onclick={on_close.clone()}>
+                {"Back"}
+            </button>
+        </section>
+    }
+}
+
+// e.g. "Sat Oct 17 2026"
+fn format_date(ms: f64) -> String {
+    js_sys::Date::new(&ms.into()).to_date_string().into()
+}
+
 // --- Logic helpers ------------------------------------------------------------
 
-fn is_answer_correct(q: &Question, selected_choice: Option<usize>) -> bool {
+// Adds a finished quiz to the history and, if the grown-up opted in, saves it.
+#[allow(clippy::too_many_arguments)]
+fn record_session(
+    story: &Story,
+    progress: &[QuestionProgress],
+    used_ai: bool,
+    level: DifficultyLevel,
+    settings: &AppSettings,
+    history: &UseStateHandle<Vec<SessionRecord>>,
+    blocked_topics: &[BlockedTopic],
+    error: &UseStateHandle<Option<String>>,
+) {
+    let mut sessions = (**history).clone();
+    sessions.push(SessionRecord {
+        story: story.clone(),
+        progress: progress.to_vec(),
+        score_percent: scoring::score_percent(progress, settings.scoring),
+        used_ai,
+        level: Some(level),
+        completed_at: storage::now_ms(),
+    });
+    if let Some(msg) = persist(settings, &sessions, blocked_topics) {
+        error.set(Some(msg));
+    }
+    history.set(sessions);
+}
+
+// Writes settings + history to localStorage when saving is turned on.
+// Returns a message for the error banner if the browser refused.
+fn persist(
+    settings: &AppSettings,
+    sessions: &[SessionRecord],
+    blocked_topics: &[BlockedTopic],
+) -> Option<String> {
+    if !settings.save_history {
+        return None;
+    }
+    let data = SavedData {
+        schema_version: storage::SCHEMA_VERSION,
+        settings: settings.clone(),
+        sessions: sessions.to_vec(),
+        blocked_topics: blocked_topics.to_vec(),
+    };
+    storage::save(&data)
+        .err()
+        .map(|e| format!("Could not save reading history: {}.", e))
+}
+
+// Untouched progress for every question of `story` (none without a story).
+fn fresh_progress(story: Option<&Story>) -> Vec<QuestionProgress> {
+    story
+        .map(|st| st.questions.iter().map(|_| QuestionProgress::new()).collect())
+        .unwrap_or_default()
+}
+
+// Credit for an answer, from 0.0 (wrong) to 1.0 (fully correct). Only
+// sequence questions award partial credit.
+fn grade_answer(
+    q: &Question,
+    selected_choice: Option<usize>,
+    typed_answer: &str,
+    sequence_order: &[usize],
+) -> f32 {
+    let full_or_nothing = |correct: bool| if correct { 1.0 } else { 0.0 };
     match &q.kind {
         QuestionKind::MultipleChoice {
             correct_index, ..
-        } => selected_choice.map(|i| i == *correct_index).unwrap_or(false),
+        } => full_or_nothing(selected_choice.map(|i| i == *correct_index).unwrap_or(false)),
+        QuestionKind::ShortAnswer {
+            accepted_answers,
+            matching,
+        } => full_or_nothing(short_answer_matches(typed_answer, accepted_answers, matching)),
+        QuestionKind::TrueFalse { answer } => {
+            // Choice 0 is "True", choice 1 is "False"
+            let expected = if *answer { 0 } else { 1 };
+            full_or_nothing(selected_choice == Some(expected))
+        }
+        QuestionKind::EvidenceSelect { correct_sentence } => {
+            full_or_nothing(selected_choice == Some(*correct_sentence))
+        }
+        QuestionKind::Sequence { correct_order, .. } => {
+            sequence_credit(sequence_order, correct_order)
+        }
+        QuestionKind::Vocabulary { correct_index, .. } => {
+            full_or_nothing(selected_choice == Some(*correct_index))
+        }
+    }
+}
+
+// Fraction of events sitting in their correct slot.
+fn sequence_credit(order: &[usize], correct_order: &[usize]) -> f32 {
+    if correct_order.is_empty() || order.len() != correct_order.len() {
+        return 0.0;
     }
+    let in_place = order
+        .iter()
+        .zip(correct_order)
+        .filter(|(a, b)| a == b)
+        .count();
+    in_place as f32 / correct_order.len() as f32
 }
 
-// --- Fallback stories ---------------------------------------------------------
+// The kid's arrangement for a sequence question, or the starting arrangement
+// when they haven't moved anything yet. If the events happen to be listed
+// already in order, the start is rotated by one so it is never pre-solved.
+fn current_sequence_order(q: &Question, sequence_order: &[usize]) -> Vec<usize> {
+    let QuestionKind::Sequence { events, correct_order } = &q.kind else {
+        return Vec::new();
+    };
+    if sequence_order.len() == events.len() {
+        return sequence_order.to_vec();
+    }
 
-fn pick_fallback_story(num_paragraphs: u8) -> Story {
-    let mut rng = thread_rng();
-    let mut stories = fallback_stories();
-    stories.shuffle(&mut rng);
-    let mut story = stories.remove(0);
+    let mut order: Vec<usize> = (0..events.len()).collect();
+    if order.len() > 1 && order == *correct_order {
+        order.rotate_left(1);
+    }
+    order
+}
 
-    let desired = num_paragraphs.clamp(1, 6) as usize;
-    if story.paragraphs.len() > desired {
-        story.paragraphs.truncate(desired);
+// A button that reads `text` aloud; nothing when narration is off.
+fn read_aloud_button(text: String, label: &'static str, narration: Option<&speech::Narration>) -> Html {
+    let Some(narration) = narration.cloned() else {
+        return html! {};
+    };
+    let onclick = Callback::from(move |_: MouseEvent| speech::speak(&text, &narration));
+    html! {
+        <button class="btn btn-small read-aloud" title="Read aloud" {onclick}>
+            { label }
+        </button>
     }
+}
 
-    story
+// What the question's read-aloud button says: the question, then the
+// choices (or events, in the kid's current order) it offers.
+fn read_aloud_text(q: &Question, sequence_order: &[usize]) -> String {
+    let mut text = q.text.clone();
+    match &q.kind {
+        QuestionKind::MultipleChoice { choices, .. } | QuestionKind::Vocabulary { choices, .. } => {
+            for (i, choice) in choices.iter().enumerate() {
+                text.push_str(&format!(" Choice {}: {}.", i + 1, choice));
+            }
+        }
+        QuestionKind::TrueFalse { .. } => text.push_str(" True or false?"),
+        QuestionKind::Sequence { events, .. } => {
+            for (pos, &i) in current_sequence_order(q, sequence_order).iter().enumerate() {
+                if let Some(event) = events.get(i) {
+                    text.push_str(&format!(" {}: {}.", pos + 1, event));
+                }
+            }
+        }
+        QuestionKind::ShortAnswer { .. } | QuestionKind::EvidenceSelect { .. } => {}
+    }
+    text
+}
+
+// Returns a prompt for the kid when nothing has been answered yet, so an
+// empty "Check Answer" click never counts as an attempt. A sequence counts as
+// unanswered until an event is moved: the starting order is never correct.
+fn missing_answer_message(
+    q: &Question,
+    selected_choice: Option<usize>,
+    typed_answer: &str,
+    sequence_order: &[usize],
+) -> Option<&'static str> {
+    match &q.kind {
+        QuestionKind::MultipleChoice { .. } if selected_choice.is_none() => {
+            Some("Please choose an answer before checking.")
+        }
+        QuestionKind::ShortAnswer { .. } if typed_answer.trim().is_empty() => {
+            Some("Please type an answer before checking.")
+        }
+        QuestionKind::Vocabulary { .. } if selected_choice.is_none() => {
+            Some("Please choose a meaning before checking.")
+        }
+        QuestionKind::TrueFalse { .. } if selected_choice.is_none() => {
+            Some("Please choose True or False before checking.")
+        }
+        QuestionKind::EvidenceSelect { .. } if selected_choice.is_none() => {
+            Some("Please tap a sentence in the highlighted paragraph before checking.")
+        }
+        QuestionKind::Sequence { events, .. }
+            if events.len() > 1 && sequence_order.is_empty() =>
+        {
+            Some("Please move the events into order before checking.")
+        }
+        _ => None,
+    }
+}
+
+fn short_answer_matches(typed: &str, accepted: &[String], rules: &AnswerMatching) -> bool {
+    let typed = normalize_answer(typed, rules);
+    if typed.is_empty() {
+        return false;
+    }
+
+    accepted.iter().any(|answer| {
+        let target = normalize_answer(answer, rules);
+        if target.is_empty() {
+            return false;
+        }
+        // Very short answers ("red", "4") must be exact; a typo there
+        // usually means a different word.
+        let allowed_typos = if target.chars().count() < 4 {
+            0
+        } else {
+            rules.max_typos
+        };
+        edit_distance(&typed, &target) <= allowed_typos
+    })
+}
+
+// Renders a paragraph with every match of `word` wrapped in <mark>, so
+// vocabulary questions can point at the word in context.
+fn highlight_word(paragraph: &str, word: &str) -> Html {
+    let mut pieces: Vec<Html> = Vec::new();
+    let mut last = 0;
+    for range in find_whole_word(paragraph, word) {
+        pieces.push(html! { { &paragraph[last..range.start] } });
+        pieces.push(html! { <mark class="vocab-word">{ &paragraph[range.clone()] }</mark> });
+        last = range.end;
+    }
+    pieces.push(html! { { &paragraph[last..] } });
+
+    html! { for pieces }
+}
+
+// Byte ranges of every whole-word, case-insensitive match of `word` in
+// `paragraph` ("ant" is not found in "giant"). Validation uses this too, so
+// a vocabulary word that passes is always highlighted.
+fn find_whole_word(paragraph: &str, word: &str) -> Vec<std::ops::Range<usize>> {
+    let needle = word.trim().to_lowercase();
+    // Lowercasing can change byte lengths for some characters ('İ' grows,
+    // the Kelvin sign shrinks); only search when every character keeps its
+    // width, so offsets in `lower` line up with the original text.
+    let same_widths = paragraph
+        .chars()
+        .all(|c| c.to_lowercase().map(char::len_utf8).sum::<usize>() == c.len_utf8());
+    if needle.is_empty() || !same_widths {
+        return Vec::new();
+    }
+
+    let lower = paragraph.to_lowercase();
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '-';
+    let mut found = Vec::new();
+    let mut search_from = 0;
+    while let Some(offset) = lower[search_from..].find(&needle) {
+        let start = search_from + offset;
+        let end = start + needle.len();
+        let before_ok = paragraph[..start].chars().last().map(|c| !is_word_char(c)).unwrap_or(true);
+        let after_ok = paragraph[end..].chars().next().map(|c| !is_word_char(c)).unwrap_or(true);
+        if before_ok && after_ok {
+            found.push(start..end);
+        }
+        search_from = end;
+    }
+    found
+}
+
+// Splits a paragraph into sentences after ".", "!" or "?" (keeping the
+// punctuation and any closing quote). Evidence questions index into this list,
+// so the Worker must count sentences the same way.
+fn split_sentences(paragraph: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut chars = paragraph.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        current.push(c);
+        if matches!(c, '.' | '!' | '?') {
+            while let Some(&next) = chars.peek() {
+                if matches!(next, '"' | '”' | '\'' | '’' | ')') {
+                    current.push(next);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let ends_sentence = chars.peek().map(|n| n.is_whitespace()).unwrap_or(true);
+            if ends_sentence && !ends_with_abbreviation(&current) {
+                let sentence = current.trim().to_string();
+                if !sentence.is_empty() {
+                    sentences.push(sentence);
+                }
+                current.clear();
+            }
+        }
+    }
+
+    let rest = current.trim();
+    if !rest.is_empty() {
+        sentences.push(rest.to_string());
+    }
+    sentences
+}
+
+// Titles like "Mr." or "Ms." end in a period but never end a sentence.
+fn ends_with_abbreviation(text: &str) -> bool {
+    const ABBREVIATIONS: [&str; 6] = ["mr.", "mrs.", "ms.", "dr.", "st.", "mt."];
+    let last_word = text.split_whitespace().last().unwrap_or("").to_lowercase();
+    ABBREVIATIONS.contains(&last_word.as_str())
+}
+
+// Lowercases, strips punctuation and stems each word according to the rules,
+// then joins the words with single spaces.
+fn normalize_answer(text: &str, rules: &AnswerMatching) -> String {
+    let mut cleaned = if rules.case_sensitive {
+        text.to_string()
+    } else {
+        text.to_lowercase()
+    };
+
+    if rules.ignore_punctuation {
+        cleaned = cleaned
+            .chars()
+            .filter(|c| *c != '\'' && *c != '’')
+            .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+            .collect();
+    }
+
+    cleaned
+        .split_whitespace()
+        .map(|word| {
+            if rules.stem_words {
+                stem_word(word)
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
-fn fallback_stories() -> Vec<Story> {
-    vec![
-        Story {
-            title: "The Science Fair Mystery".into(),
-            paragraphs: vec![
-                "Maya loved science more than anything. When her school announced a science fair, she decided to build a tiny wind turbine that could power a small light bulb.".into(),
-                "For weeks, she tested different blade shapes in front of a fan. Some blades barely moved, but others spun so fast that the bulb flickered to life.".into(),
-                "On the day of the fair, Maya discovered that her project table had been bumped, and her blades were scattered on the floor. She stayed calm, rebuilt the turbine, and showed the judges how testing and patience helped her design improve.".into(),
-            ],
-            questions: vec![
-                Question {
-                    text: "What was Maya building for the science fair?".into(),
-                    paragraph_index: 0,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "A robot that could talk".into(),
-                            "A tiny wind turbine".into(),
-                            "A solar-powered car".into(),
-                            "A model of the solar system".into(),
-                        ],
-                        correct_index: 1,
-                    },
-                },
-                Question {
-                    text: "Where did Maya test her different blade shapes?".into(),
-                    paragraph_index: 1,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "In a swimming pool".into(),
-                            "In front of a fan".into(),
-                            "On the school roof".into(),
-                            "In the gym".into(),
-                        ],
-                        correct_index: 1,
-                    },
-                },
-                Question {
-                    text: "How did Maya react when she saw her blades on the floor?".into(),
-                    paragraph_index: 2,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "She shouted at her classmates".into(),
-                            "She went home and quit the fair".into(),
-                            "She stayed calm and rebuilt the turbine".into(),
-                            "She asked the judges to skip her project".into(),
-                        ],
-                        correct_index: 2,
-                    },
-                },
-            ],
-        },
-        Story {
-            title: "The Lost Backpack on the Bus".into(),
-            paragraphs: vec![
-                "Jamal always double-checked his backpack before leaving school. One rainy afternoon, he rushed to catch the bus and forgot to zip it closed.".into(),
-                "On the ride home, the bus bumped over a pothole. Jamal’s notebook slid out of his open backpack and under the seat without him noticing.".into(),
-                "When he got home, Jamal realized his notebook was missing. He thought carefully about his day and remembered the bump on the bus, so he called the bus driver and they found the notebook under the seat.".into(),
-            ],
-            questions: vec![
-                Question {
-                    text: "What did Jamal forget to do before he got on the bus?".into(),
-                    paragraph_index: 0,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "Put on his shoes".into(),
-                            "Zip his backpack".into(),
-                            "Finish his homework".into(),
-                            "Call his friend".into(),
-                        ],
-                        correct_index: 1,
-                    },
-                },
-                Question {
-                    text: "Where did the notebook go when the bus hit the pothole?".into(),
-                    paragraph_index: 1,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "Out the window".into(),
-                            "Into another student’s backpack".into(),
-                            "Under the seat".into(),
-                            "Onto the driver’s chair".into(),
-                        ],
-                        correct_index: 2,
-                    },
-                },
-                Question {
-                    text: "How did Jamal finally find his notebook?".into(),
-                    paragraph_index: 2,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "He searched the school hallway".into(),
-                            "He called the bus driver".into(),
-                            "His teacher brought it home".into(),
-                            "A friend mailed it to him".into(),
-                        ],
-                        correct_index: 1,
-                    },
-                },
-            ],
-        },
-        Story {
-            title: "The Classroom Garden".into(),
-            paragraphs: vec![
-                "Ms. Lopez brought small pots, soil, and seeds to class. She told her students they would grow a mini garden on the windowsill.".into(),
-                "Each student planted a seed and wrote their name on the pot. Some seeds sprouted quickly, while others took more time to peek through the soil.".into(),
-                "When one student’s seed did not sprout, the class worked together to check the soil, water, and sunlight. They planted a new seed, and the student learned that plants sometimes need a second chance too.".into(),
-            ],
-            questions: vec![
-                Question {
-                    text: "Where did the class keep their mini garden?".into(),
-                    paragraph_index: 0,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "On the playground".into(),
-                            "In the gym".into(),
-                            "On the windowsill".into(),
-                            "In the cafeteria".into(),
-                        ],
-                        correct_index: 2,
-                    },
-                },
-                Question {
-                    text: "What did each student write on their pot?".into(),
-                    paragraph_index: 1,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "A science question".into(),
-                            "A funny joke".into(),
-                            "Their favorite color".into(),
-                            "Their name".into(),
-                        ],
-                        correct_index: 3,
-                    },
-                },
-                Question {
-                    text: "What did the class do when one seed did not sprout?".into(),
-                    paragraph_index: 2,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "They threw the pot away".into(),
-                            "They ignored it".into(),
-                            "They checked the plant’s needs and tried again".into(),
-                            "They stopped watering all the plants".into(),
-                        ],
-                        correct_index: 2,
-                    },
-                },
-            ],
-        },
-        Story {
-            title: "The Library Map Challenge".into(),
-            paragraphs: vec![
-                "The school librarian, Mr. Lee, created a map of the library with clues. He told the class they would use the map to find a hidden box of bookmarks.".into(),
-                "The map showed different sections, like history, science, and sports. Each clue led to a new shelf and taught the students how books were organized.".into(),
-                "When the class finally found the hidden box, Mr. Lee explained that learning to read maps could help them explore both books and the real world.".into(),
-            ],
-            questions: vec![
-                Question {
-                    text: "What did the map in the library lead to?".into(),
-                    paragraph_index: 0,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "A secret doorway".into(),
-                            "A hidden box of bookmarks".into(),
-                            "A new computer lab".into(),
-                            "A stack of comic books".into(),
-                        ],
-                        correct_index: 1,
-                    },
-                },
-                Question {
-                    text: "Which section was mentioned on the library map?".into(),
-                    paragraph_index: 1,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "Weather".into(),
-                            "History".into(),
-                            "Cooking".into(),
-                            "Music videos".into(),
-                        ],
-                        correct_index: 1,
-                    },
-                },
-                Question {
-                    text: "What did Mr. Lee want students to learn from the map challenge?".into(),
-                    paragraph_index: 2,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "How to whisper quietly".into(),
-                            "How to walk faster".into(),
-                            "How to read maps and explore".into(),
-                            "How to put books on the floor".into(),
-                        ],
-                        correct_index: 2,
-                    },
-                },
-            ],
-        },
-        Story {
-            title: "The Rainy Day Coding Club".into(),
-            paragraphs: vec![
-                "On a rainy Friday, the after-school coding club met in the computer lab. Their challenge was to program a character to move through a simple maze.".into(),
-                "At first, the character kept bumping into walls. The students tested different commands, like turn, move forward, and repeat, until the character reached the goal.".into(),
-                "By the end of the club, the students realized that fixing mistakes was a normal part of coding, and each error had helped them understand the maze better.".into(),
-            ],
-            questions: vec![
-                Question {
-                    text: "What was the challenge at the coding club?".into(),
-                    paragraph_index: 0,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "Build a robot dog".into(),
-                            "Program a character to move through a maze".into(),
-                            "Design a new video game console".into(),
-                            "Write a story about coding".into(),
-                        ],
-                        correct_index: 1,
-                    },
-                },
-                Question {
-                    text: "Which type of commands did the students test?".into(),
-                    paragraph_index: 1,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "Sing and dance".into(),
-                            "Turn, move forward, and repeat".into(),
-                            "Jump and spin".into(),
-                            "Erase and redraw".into(),
-                        ],
-                        correct_index: 1,
-                    },
-                },
-                Question {
-                    text: "What did the students learn about mistakes in coding?".into(),
-                    paragraph_index: 2,
-                    kind: QuestionKind::MultipleChoice {
-                        choices: vec![
-                            "Mistakes mean you should quit".into(),
-                            "Mistakes are normal and help you learn".into(),
-                            "Only teachers can fix mistakes".into(),
-                            "Mistakes always break the computer".into(),
-                        ],
-                        correct_index: 1,
-                    },
-                },
-            ],
-        },
-    ]
+// Very small suffix stripper: enough to treat "planted" / "planting" /
+// "plants" as "plant" without pulling in a stemming crate. "es" is only a
+// suffix after s, x, z, ch or sh ("boxes"); "notes" just loses the "s".
+fn stem_word(word: &str) -> String {
+    const SUFFIXES: [&str; 6] = ["ing", "ed", "es", "ly", "er", "s"];
+    const SIBILANTS: [&str; 5] = ["s", "x", "z", "ch", "sh"];
+    for suffix in SUFFIXES {
+        if let Some(stem) = word.strip_suffix(suffix) {
+            if suffix == "es" && !SIBILANTS.iter().any(|s| stem.ends_with(s)) {
+                continue;
+            }
+            if stem.chars().count() >= 3 {
+                return stem.to_string();
+            }
+        }
+    }
+    word.to_string()
+}
+
+// Classic Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        curr[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            curr[j] = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
 }
 
 // -----------------------------------------------------------------------------
 
 fn main() {
     yew::Renderer::<App>::new().render();
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn stem_word_strips_common_suffixes() {
+        assert_eq!(stem_word("planted"), "plant");
+        assert_eq!(stem_word("planting"), "plant");
+        assert_eq!(stem_word("plants"), "plant");
+        assert_eq!(stem_word("quickly"), "quick");
+        assert_eq!(stem_word("boxes"), "box");
+        assert_eq!(stem_word("wishes"), "wish");
+        assert_eq!(stem_word("notes"), "note");
+        assert_eq!(stem_word("goes"), "goe");
+        // Too short a stem is left alone
+        assert_eq!(stem_word("bus"), "bus");
+        assert_eq!(stem_word("red"), "red");
+    }
+
+    #[test]
+    fn edit_distance_counts_single_char_edits() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("cat", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("seed", "sed"), 1);
+        assert_eq!(edit_distance("niño", "nino"), 1);
+    }
+
+    #[test]
+    fn short_answers_forgive_case_punctuation_endings_and_a_typo() {
+        let rules = AnswerMatching::default();
+        let accepted = answers(&["planted seeds", "the garden"]);
+        assert!(short_answer_matches(
+            "Planting the seeds!",
+            &answers(&["planting the seeds"]),
+            &rules
+        ));
+        assert!(short_answer_matches("plants seed", &accepted, &rules));
+        assert!(short_answer_matches("The Gardn.", &accepted, &rules));
+        assert!(!short_answer_matches("the park", &accepted, &rules));
+        assert!(!short_answer_matches("   ", &accepted, &rules));
+    }
+
+    #[test]
+    fn short_answers_do_not_match_a_different_word() {
+        let rules = AnswerMatching::default();
+        assert_eq!(normalize_answer("Notes!", &rules), "note");
+        assert!(short_answer_matches("note", &answers(&["notes"]), &rules));
+        assert!(!short_answer_matches("nosy", &answers(&["notes"]), &rules));
+        // Answers under four letters must be exact
+        assert!(!short_answer_matches("rad", &answers(&["red"]), &rules));
+        assert!(!short_answer_matches("anything", &answers(&[" "]), &rules));
+    }
+
+    #[test]
+    fn short_answer_rules_can_be_strict() {
+        let rules = AnswerMatching {
+            case_sensitive: true,
+            ignore_punctuation: false,
+            stem_words: false,
+            max_typos: 0,
+        };
+        let accepted = answers(&["Mars"]);
+        assert!(short_answer_matches("Mars", &accepted, &rules));
+        assert!(!short_answer_matches("mars", &accepted, &rules));
+        assert!(!short_answer_matches("Mars!", &accepted, &rules));
+        assert!(!short_answer_matches("Marss", &accepted, &rules));
+    }
+
+    #[test]
+    fn sentences_split_after_end_punctuation_but_not_titles() {
+        assert_eq!(
+            split_sentences("Mr. Lee ran. He won!"),
+            vec!["Mr. Lee ran.", "He won!"]
+        );
+        assert_eq!(
+            split_sentences("\"Look!\" said Dr. Kim. Is it 3.5 feet? Yes"),
+            vec!["\"Look!\"", "said Dr. Kim.", "Is it 3.5 feet?", "Yes"]
+        );
+        assert_eq!(split_sentences("  "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn abbreviations_are_recognized_at_the_end_only() {
+        assert!(ends_with_abbreviation("We met Mrs."));
+        assert!(ends_with_abbreviation("St."));
+        assert!(!ends_with_abbreviation("He ran."));
+    }
+
+    fn sequence(events: usize, correct_order: Vec<usize>) -> Question {
+        Question {
+            text: "Put these in order.".into(),
+            paragraph_index: 0,
+            kind: QuestionKind::Sequence {
+                events: (0..events).map(|i| format!("Event {}", i)).collect(),
+                correct_order,
+            },
+        }
+    }
+
+    #[test]
+    fn sequence_credit_counts_events_in_their_slot() {
+        assert_eq!(sequence_credit(&[2, 0, 1, 3], &[2, 0, 1, 3]), 1.0);
+        // One swapped pair: two of four events are out of place
+        assert_eq!(sequence_credit(&[0, 2, 1, 3], &[2, 0, 1, 3]), 0.5);
+        assert_eq!(sequence_credit(&[0, 1], &[1, 0]), 0.0);
+        assert_eq!(sequence_credit(&[0, 1], &[0, 1, 2]), 0.0);
+        assert_eq!(sequence_credit(&[], &[]), 0.0);
+    }
+
+    #[test]
+    fn sequence_start_is_never_already_solved() {
+        let q = sequence(3, vec![0, 1, 2]);
+        assert_eq!(current_sequence_order(&q, &[]), vec![1, 2, 0]);
+        assert_eq!(current_sequence_order(&q, &[2, 1, 0]), vec![2, 1, 0]);
+        let q = sequence(3, vec![2, 0, 1]);
+        assert_eq!(current_sequence_order(&q, &[]), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn unmoved_sequence_is_not_checked() {
+        let q = sequence(3, vec![2, 0, 1]);
+        assert!(missing_answer_message(&q, None, "", &[]).is_some());
+        assert!(missing_answer_message(&q, None, "", &[2, 0, 1]).is_none());
+        // A single event has nothing to move
+        assert!(missing_answer_message(&sequence(1, vec![0]), None, "", &[]).is_none());
+    }
+
+    #[test]
+    fn whole_words_are_found_ignoring_case() {
+        assert_eq!(
+            find_whole_word("An ant met a giant Ant.", "ant"),
+            vec![3..6, 19..22]
+        );
+        assert!(find_whole_word("A giant!", "ant").is_empty());
+        assert!(find_whole_word("A well-known ant.", "known").is_empty());
+        assert!(find_whole_word("A fox.", " ").is_empty());
+        // Offsets would not line up after lowercasing, so nothing is found
+        assert!(find_whole_word("İ saw an ant.", "ant").is_empty());
+    }
+}
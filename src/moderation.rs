@@ -0,0 +1,697 @@
+// E:\rust_dev\kids_story_trainer\src\moderation.rs
+//
+// Offline topic moderation. Topics are checked against a blocklist before
+// any network call; matches are whole words (after undoing leetspeak and
+// s p a c e d letters, and allowing a plain plural), and an allowlist of
+// phrases like "killer whale" wins over the blocklist. Other word forms are
+// listed explicitly, so "shooting" is blocked but "shoot" never matches
+// "shooting star" by accident. Teachers can edit both lists in Settings.
+//
+// Generated stories get a second pass (`ContentRules::screen`) before the
+// child sees them: the same word matching over the title, paragraphs and
+// questions, plus a rough check for words far above the grade level.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{QuestionKind, Story};
+
+// Shown instead of the blocked topic, with a few safe ideas.
+const TOPIC_SUGGESTIONS: [&str; 3] = ["animals", "outer space", "the ocean"];
+
+// Blocked attempts kept for the teacher (oldest dropped first).
+pub const MAX_BLOCKED_LOG: usize = 50;
+
+// Default word lists, shared by the topic blocklist and story screening.
+const VIOLENT_WORDS: &[&str] = &[
+    "kill", "killed", "killing", "killer", "murder", "murdered", "murderer", "gun", "shoot",
+    "shooting", "shooter", "stab", "stabbed", "stabbing", "blood", "bloody", "gore", "weapon",
+    "bomb", "bombing", "corpse", "torture", "tortured", "strangle", "strangled", "suicide",
+    "terrorist",
+];
+const GROWN_UP_WORDS: &[&str] = &[
+    "drugs", "cocaine", "alcohol", "beer", "wine", "drunk", "vape", "vaping", "cigarette", "sex",
+    "sexy", "naked", "nude", "porn",
+];
+// Fine as a story topic ("the dumbest dinosaur jokes") but not in a story.
+const RUDE_WORDS: &[&str] = &["stupid", "idiot", "dumb"];
+
+// Symbols that stand in for letters ("k@ll", "$ex"); words keep them so
+// they can be read back as letters.
+const LEET_SYMBOLS: &str = "@$";
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct TopicFilter {
+    pub blocked: Vec<String>,
+    pub allowed: Vec<String>,
+}
+
+impl Default for TopicFilter {
+    fn default() -> Self {
+        let list = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+        TopicFilter {
+            blocked: list(&[VIOLENT_WORDS, GROWN_UP_WORDS].concat()),
+            allowed: list(&[
+                "killer whale", "killer bee", "shooting star", "water gun", "blood cells",
+                "bath bomb", "naked mole rat",
+            ]),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum TopicVerdict {
+    Allowed,
+    // `matched` is the blocklist entry that matched, for the teacher's log.
+    Blocked { matched: String },
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct BlockedTopic {
+    pub topic: String,
+    pub matched: String,
+    // Milliseconds since the Unix epoch (JS `Date.now()`).
+    pub at: f64,
+}
+
+impl TopicFilter {
+    pub fn check(&self, topic: &str) -> TopicVerdict {
+        let words = normalize_words(topic);
+
+        // Positions covered by an allowed phrase can't trigger a block.
+        let mut allowed = vec![false; words.len()];
+        for phrase in &self.allowed {
+            for range in find_phrase(&words, &normalize_words(phrase)) {
+                allowed[range].iter_mut().for_each(|a| *a = true);
+            }
+        }
+
+        for entry in &self.blocked {
+            let phrase = normalize_words(entry);
+            if find_phrase(&words, &phrase).any(|range| !allowed[range].iter().all(|a| *a)) {
+                return TopicVerdict::Blocked {
+                    matched: entry.clone(),
+                };
+            }
+        }
+        TopicVerdict::Allowed
+    }
+}
+
+// Kid-facing message; never repeats the blocked word.
+pub fn friendly_message() -> String {
+    format!(
+        "Let's pick a different topic for a school story! How about {}, {} or {}?",
+        TOPIC_SUGGESTIONS[0], TOPIC_SUGGESTIONS[1], TOPIC_SUGGESTIONS[2]
+    )
+}
+
+// One entry per line (commas also work) for the Settings text boxes.
+pub fn parse_list(text: &str) -> Vec<String> {
+    text.split(['\n', ','])
+        .map(|w| w.trim().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+// Lowercased, de-leeted words without a plural ending. Runs of single
+// letters are joined so "k i l l" and "k.i.l.l" read as "kill".
+fn normalize_words(text: &str) -> Vec<String> {
+    let cleaned: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || LEET_SYMBOLS.contains(c) { c } else { ' ' })
+        .collect();
+
+    let mut words: Vec<String> = Vec::new();
+    let mut letters = String::new();
+    for token in cleaned.split_whitespace() {
+        let token = undo_leetspeak(token);
+        if token.chars().count() == 1 {
+            letters.push_str(&token);
+            continue;
+        }
+        flush_letters(&mut letters, &mut words);
+        words.push(token);
+    }
+    flush_letters(&mut letters, &mut words);
+
+    words.iter().map(|w| plural_key(w)).collect()
+}
+
+// Strips only a plain plural ("guns" -> "gun", "boxes" -> "box"); other
+// endings are left alone so a list entry never matches a different word
+// that happens to start the same way.
+fn plural_key(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix("es") {
+        if ["ss", "x", "z", "ch", "sh"].iter().any(|end| stem.ends_with(end)) {
+            return stem.to_string();
+        }
+    }
+    match word.strip_suffix('s') {
+        Some(stem) if stem.chars().count() >= 2 && !stem.ends_with('s') => stem.to_string(),
+        _ => word.to_string(),
+    }
+}
+
+fn flush_letters(letters: &mut String, words: &mut Vec<String>) {
+    if letters.chars().count() >= 3 {
+        words.push(undo_leetspeak(letters));
+        letters.clear();
+    } else {
+        // "a", "i", "5 dogs": keep as ordinary words
+        words.extend(letters.chars().map(String::from));
+        letters.clear();
+    }
+}
+
+// Only tokens that contain a letter are translated, so "top 10 sharks"
+// stays as it is but "k1ll" becomes "kill".
+fn undo_leetspeak(token: &str) -> String {
+    if !token.chars().any(|c| c.is_alphabetic()) {
+        return token.to_string();
+    }
+    token
+        .chars()
+        .map(|c| match c {
+            '0' => 'o',
+            '1' => 'i',
+            '3' => 'e',
+            '4' | '@' => 'a',
+            '5' | '$' => 's',
+            '7' => 't',
+            _ => c,
+        })
+        .collect()
+}
+
+// Every place `phrase` appears as consecutive whole words.
+fn find_phrase<'a>(
+    words: &'a [String],
+    phrase: &'a [String],
+) -> impl Iterator<Item = std::ops::Range<usize>> + 'a {
+    let len = phrase.len();
+    (0..words.len().saturating_sub(len.max(1) - 1))
+        .filter(move |&i| len > 0 && words[i..i + len] == *phrase)
+        .map(move |i| i..i + len)
+}
+
+// --- Story screening ---
+
+// Letters in the longest word we expect at grade 0; each grade adds one.
+const LONG_WORD_BASE: usize = 8;
+// More long words than this (and at least a few) means "above grade".
+const MAX_LONG_WORD_PERCENT: usize = 4;
+const MIN_LONG_WORDS: usize = 3;
+
+const MASK: &str = "•••";
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreeningAction {
+    // Use a built-in story instead.
+    #[default]
+    Reject,
+    // Hide the flagged words and keep the story.
+    Mask,
+}
+
+impl ScreeningAction {
+    pub const ALL: [ScreeningAction; 2] = [ScreeningAction::Reject, ScreeningAction::Mask];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            ScreeningAction::Reject => "reject",
+            ScreeningAction::Mask => "mask",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<ScreeningAction> {
+        ScreeningAction::ALL.into_iter().find(|a| a.key() == key)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScreeningAction::Reject => "Use a built-in story instead",
+            ScreeningAction::Mask => "Hide the flagged words",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct ContentRules {
+    pub action: ScreeningAction,
+    pub violence: Vec<String>,
+    pub scary: Vec<String>,
+    pub mature: Vec<String>,
+    // Teacher's own additions.
+    pub custom: Vec<String>,
+    // Phrases that are fine even though a word in them is flagged.
+    pub allowed: Vec<String>,
+}
+
+impl Default for ContentRules {
+    fn default() -> Self {
+        let list = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+        ContentRules {
+            action: ScreeningAction::default(),
+            violence: list(VIOLENT_WORDS),
+            scary: list(&["demon", "zombie", "horror", "terror", "possessed", "nightmare"]),
+            mature: list(&[GROWN_UP_WORDS, RUDE_WORDS].concat()),
+            custom: Vec::new(),
+            allowed: TopicFilter::default().allowed,
+        }
+    }
+}
+
+// Where in the story a problem was found.
+#[derive(Clone, PartialEq, Debug)]
+pub enum StoryPart {
+    Title,
+    Paragraph(usize),
+    Question(usize),
+}
+
+impl fmt::Display for StoryPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoryPart::Title => write!(f, "the title"),
+            StoryPart::Paragraph(i) => write!(f, "paragraph {}", i + 1),
+            StoryPart::Question(i) => write!(f, "Q{}", i + 1),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ContentFlag {
+    // The word itself is kept for debugging but never shown to the child.
+    Word {
+        category: &'static str,
+        word: String,
+        part: StoryPart,
+    },
+    AboveGrade {
+        long_words: usize,
+        total_words: usize,
+    },
+}
+
+// Kid-safe wording: names the category and place, not the word.
+impl fmt::Display for ContentFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentFlag::Word { category, part, .. } => write!(f, "{} word in {}", category, part),
+            ContentFlag::AboveGrade {
+                long_words,
+                total_words,
+            } => write!(
+                f,
+                "{} of {} words look too hard for this grade",
+                long_words, total_words
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ScreeningReport {
+    pub flags: Vec<ContentFlag>,
+}
+
+impl ScreeningReport {
+    pub fn is_clean(&self) -> bool {
+        self.flags.is_empty()
+    }
+
+    // One line for the error banner, like `ValidationReport::summary`.
+    pub fn summary(&self) -> String {
+        const MAX_LISTED: usize = 3;
+        let mut parts: Vec<String> = self
+            .flags
+            .iter()
+            .take(MAX_LISTED)
+            .map(|f| f.to_string())
+            .collect();
+        if self.flags.len() > MAX_LISTED {
+            parts.push(format!("{} more", self.flags.len() - MAX_LISTED));
+        }
+        parts.join("; ")
+    }
+}
+
+impl ContentRules {
+    fn categories(&self) -> [(&'static str, &[String]); 4] {
+        [
+            ("violent", &self.violence),
+            ("scary", &self.scary),
+            ("grown-up", &self.mature),
+            ("blocked", &self.custom),
+        ]
+    }
+
+    // Flagged words or phrases in `text` (as slices of it, in order and
+    // never overlapping) with their category. Entries match like the topic
+    // blocklist: whole words, and a phrase entry only as the whole phrase.
+    // Matches entirely inside an allowed phrase are skipped.
+    fn flagged_words<'t>(&self, text: &'t str) -> Vec<(&'t str, &'static str)> {
+        let words: Vec<&str> = text_words(text).collect();
+        let keys: Vec<String> = words
+            .iter()
+            .map(|w| plural_key(&undo_leetspeak(&w.to_lowercase())))
+            .collect();
+
+        let mut allowed = vec![false; words.len()];
+        for phrase in &self.allowed {
+            for range in find_phrase(&keys, &normalize_words(phrase)) {
+                allowed[range].iter_mut().for_each(|a| *a = true);
+            }
+        }
+
+        // Longest match starting at each word; earlier categories win ties.
+        let mut matches: Vec<Option<(usize, &'static str)>> = vec![None; words.len()];
+        for (category, list) in self.categories() {
+            for entry in list {
+                let phrase = normalize_words(entry);
+                for range in find_phrase(&keys, &phrase) {
+                    if allowed[range.clone()].iter().all(|a| *a) {
+                        continue;
+                    }
+                    let slot = &mut matches[range.start];
+                    if slot.is_none_or(|(end, _)| range.end > end) {
+                        *slot = Some((range.end, category));
+                    }
+                }
+            }
+        }
+
+        let offset = |w: &str| w.as_ptr() as usize - text.as_ptr() as usize;
+        let mut flagged = Vec::new();
+        let mut next_free = 0;
+        for (start, found) in matches.into_iter().enumerate() {
+            let Some((end, category)) = found else {
+                continue;
+            };
+            if start < next_free {
+                continue;
+            }
+            let last = words[end - 1];
+            let span = &text[offset(words[start])..offset(last) + last.len()];
+            flagged.push((span, category));
+            next_free = end;
+        }
+        flagged
+    }
+
+    pub fn screen(&self, story: &Story, grade_level: u8) -> ScreeningReport {
+        let mut report = ScreeningReport::default();
+        for (part, text) in story_texts(story) {
+            for (word, category) in self.flagged_words(text) {
+                report.flags.push(ContentFlag::Word {
+                    category,
+                    word: word.to_string(),
+                    part: part.clone(),
+                });
+            }
+        }
+
+        // Vocabulary questions are meant to stretch, so only the passage counts.
+        let long_limit = LONG_WORD_BASE + grade_level as usize;
+        let words: Vec<&str> = story.paragraphs.iter().flat_map(|p| text_words(p)).collect();
+        let long_words = words.iter().filter(|w| w.chars().count() > long_limit).count();
+        if long_words >= MIN_LONG_WORDS && long_words * 100 > words.len() * MAX_LONG_WORD_PERCENT {
+            report.flags.push(ContentFlag::AboveGrade {
+                long_words,
+                total_words: words.len(),
+            });
+        }
+        report
+    }
+
+    // Screens a generated story and applies `action`. Err means the story
+    // must not be shown; either way the report says what was found.
+    pub fn apply(
+        &self,
+        story: Story,
+        grade_level: u8,
+    ) -> Result<(Story, ScreeningReport), ScreeningReport> {
+        let report = self.screen(&story, grade_level);
+        if report.is_clean() {
+            return Ok((story, report));
+        }
+        match self.action {
+            ScreeningAction::Reject => Err(report),
+            ScreeningAction::Mask => match self.mask(story).validate() {
+                Ok((story, _)) => Ok((story, report)),
+                Err(_) => Err(report),
+            },
+        }
+    }
+
+    // Replaces flagged words everywhere. Questions that need a flagged word
+    // to be answered (typed answers, vocabulary words) are dropped; the
+    // caller should re-validate, since masking can make choices identical.
+    pub fn mask(&self, mut story: Story) -> Story {
+        story.title = self.mask_text(&story.title);
+        story.paragraphs = story.paragraphs.iter().map(|p| self.mask_text(p)).collect();
+
+        story.questions.retain(|q| match &q.kind {
+            QuestionKind::ShortAnswer {
+                accepted_answers, ..
+            } => accepted_answers
+                .iter()
+                .all(|a| self.flagged_words(a).is_empty()),
+            QuestionKind::Vocabulary { word, .. } => self.flagged_words(word).is_empty(),
+            _ => true,
+        });
+        for q in &mut story.questions {
+            q.text = self.mask_text(&q.text);
+            match &mut q.kind {
+                QuestionKind::MultipleChoice { choices, .. }
+                | QuestionKind::Vocabulary { choices, .. } => {
+                    *choices = choices.iter().map(|c| self.mask_text(c)).collect();
+                }
+                QuestionKind::Sequence { events, .. } => {
+                    *events = events.iter().map(|e| self.mask_text(e)).collect();
+                }
+                QuestionKind::ShortAnswer { .. }
+                | QuestionKind::TrueFalse { .. }
+                | QuestionKind::EvidenceSelect { .. } => {}
+            }
+        }
+        story
+    }
+
+    fn mask_text(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for (word, _) in self.flagged_words(text) {
+            // `word` is a sub-slice of `text`, so its offset is exact.
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            out.push_str(&text[last..start]);
+            out.push_str(MASK);
+            last = start + word.len();
+        }
+        out.push_str(&text[last..]);
+        out
+    }
+}
+
+// Every piece of text a child will read, labelled with where it is.
+fn story_texts(story: &Story) -> Vec<(StoryPart, &str)> {
+    let mut texts = vec![(StoryPart::Title, story.title.as_str())];
+    for (i, p) in story.paragraphs.iter().enumerate() {
+        texts.push((StoryPart::Paragraph(i), p));
+    }
+    for (i, q) in story.questions.iter().enumerate() {
+        let part = StoryPart::Question(i);
+        texts.push((part.clone(), &q.text));
+        let extra: Vec<&String> = match &q.kind {
+            QuestionKind::MultipleChoice { choices, .. } => choices.iter().collect(),
+            QuestionKind::Vocabulary { word, choices, .. } => {
+                std::iter::once(word).chain(choices.iter()).collect()
+            }
+            QuestionKind::ShortAnswer {
+                accepted_answers, ..
+            } => accepted_answers.iter().collect(),
+            QuestionKind::Sequence { events, .. } => events.iter().collect(),
+            QuestionKind::TrueFalse { .. } | QuestionKind::EvidenceSelect { .. } => Vec::new(),
+        };
+        texts.extend(extra.into_iter().map(|t| (part.clone(), t.as_str())));
+    }
+    texts
+}
+
+// Words as slices of `text` (letters, digits and leetspeak symbols).
+fn text_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphanumeric() || LEET_SYMBOLS.contains(c)))
+        .filter(|w| !w.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocked(topic: &str) -> bool {
+        TopicFilter::default().check(topic) != TopicVerdict::Allowed
+    }
+
+    #[test]
+    fn everyday_topics_are_allowed() {
+        for topic in [
+            "shooting stars",
+            "killer bees",
+            "killer whales",
+            "skills for soccer",
+            "gunther the guinea pig",
+            "the drugstore on main street",
+            "top 10 sharks",
+            "a bath bomb",
+        ] {
+            assert!(!blocked(topic), "{} should be allowed", topic);
+        }
+    }
+
+    #[test]
+    fn listed_words_and_plurals_are_blocked() {
+        assert_eq!(
+            TopicFilter::default().check("guns and bombs"),
+            TopicVerdict::Blocked {
+                matched: "gun".into()
+            }
+        );
+        for topic in [
+            "a shooting at school",
+            "the killer",
+            "cigarettes",
+            "k1ll",
+            "k i l l",
+        ] {
+            assert!(blocked(topic), "{} should be blocked", topic);
+        }
+    }
+
+    #[test]
+    fn allowed_phrase_only_covers_its_own_words() {
+        assert!(blocked("killer whale with a gun"));
+        assert!(!blocked("killer whale"));
+    }
+
+    #[test]
+    fn plural_key_strips_only_plain_plurals() {
+        assert_eq!(plural_key("guns"), "gun");
+        assert_eq!(plural_key("boxes"), "box");
+        assert_eq!(plural_key("horses"), "horse");
+        assert_eq!(plural_key("glass"), "glass");
+        assert_eq!(plural_key("shooting"), "shooting");
+        assert_eq!(plural_key("is"), "is");
+    }
+
+    #[test]
+    fn parse_list_trims_and_lowercases() {
+        assert_eq!(
+            parse_list(" Gun\n\nSword, Bow "),
+            vec!["gun", "sword", "bow"]
+        );
+    }
+
+    fn story(paragraphs: &[&str]) -> Story {
+        Story {
+            title: "A Day Out".into(),
+            paragraphs: paragraphs.iter().map(|p| p.to_string()).collect(),
+            questions: vec![crate::Question {
+                text: "Was it fun?".into(),
+                paragraph_index: 0,
+                kind: QuestionKind::TrueFalse { answer: true },
+            }],
+            grade_level: None,
+        }
+    }
+
+    #[test]
+    fn screening_flags_words_by_category_without_naming_them() {
+        let report = ContentRules::default().screen(&story(&["The zombie had a gun."]), 3);
+        let shown: Vec<String> = report.flags.iter().map(|f| f.to_string()).collect();
+        assert_eq!(
+            shown,
+            vec!["scary word in paragraph 1", "violent word in paragraph 1"]
+        );
+    }
+
+    #[test]
+    fn screening_leaves_allowed_phrases_and_other_words_alone() {
+        let st = story(&["We saw a shooting star and a killer whale. The skills class was fun."]);
+        assert!(ContentRules::default().screen(&st, 3).is_clean());
+    }
+
+    #[test]
+    fn custom_words_are_flagged() {
+        let rules = ContentRules {
+            custom: vec!["broccoli".into()],
+            ..ContentRules::default()
+        };
+        assert!(!rules.screen(&story(&["We ate broccoli."]), 3).is_clean());
+    }
+
+    #[test]
+    fn custom_phrases_match_as_whole_phrases() {
+        let rules = ContentRules {
+            action: ScreeningAction::Mask,
+            custom: parse_list("scary clown"),
+            ..ContentRules::default()
+        };
+        assert!(rules
+            .screen(&story(&["A clown was not scary."]), 3)
+            .is_clean());
+        let (masked, _) = rules
+            .apply(story(&["The Scary  Clowns waved."]), 3)
+            .unwrap();
+        assert_eq!(masked.paragraphs[0], format!("The {} waved.", MASK));
+    }
+
+    #[test]
+    fn topics_and_stories_read_words_the_same_way() {
+        assert!(blocked("$ex"));
+        assert!(!blocked("Help!"));
+        let rules = ContentRules::default();
+        assert!(!rules
+            .screen(&story(&["$ex is not for kids."]), 3)
+            .is_clean());
+        assert!(rules
+            .screen(&story(&["Help! The kite is stuck!"]), 3)
+            .is_clean());
+    }
+
+    #[test]
+    fn masking_hides_only_the_flagged_words() {
+        let rules = ContentRules {
+            action: ScreeningAction::Mask,
+            ..ContentRules::default()
+        };
+        let (masked, report) = rules.apply(story(&["The zombie danced."]), 3).unwrap();
+        assert_eq!(masked.paragraphs[0], format!("The {} danced.", MASK));
+        assert_eq!(report.flags.len(), 1);
+    }
+
+    #[test]
+    fn reject_refuses_a_flagged_story() {
+        assert!(ContentRules::default()
+            .apply(story(&["The zombie danced."]), 3)
+            .is_err());
+    }
+
+    #[test]
+    fn long_words_flag_a_story_above_grade() {
+        let hard = "Extraordinarily magnificent archaeological expeditions uncovered \
+            unbelievable treasures.";
+        let report = ContentRules::default().screen(&story(&[hard]), 0);
+        assert!(matches!(
+            report.flags.as_slice(),
+            [ContentFlag::AboveGrade { .. }]
+        ));
+        assert!(ContentRules::default()
+            .screen(&story(&[hard]), 8)
+            .is_clean());
+    }
+}
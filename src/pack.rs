@@ -0,0 +1,301 @@
+// E:\rust_dev\kids_story_trainer\src\pack.rs
+//
+// Story packs: teacher-made collections of stories that can be shared as a
+// file and imported into the built-in library. The format is documented in
+// the README; the same shape works as JSON or TOML. Every story is validated
+// on import, exactly like an AI story, and unusable ones are left out.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Story;
+
+pub const PACK_FORMAT: &str = "kids-story-pack";
+// Bump when the pack shape changes in a way older apps can't read.
+pub const PACK_VERSION: u32 = 1;
+
+// Library pack that stories edited from the reading screen are saved into.
+pub const EDITED_PACK_NAME: &str = "Edited stories";
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct StoryPack {
+    pub format: String,
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub license: String,
+    #[serde(default)]
+    pub description: String,
+    pub stories: Vec<PackStory>,
+}
+
+// A story plus its metadata. `grade_level` comes from the story itself and
+// is required in packs; author and license default to the pack's.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct PackStory {
+    #[serde(flatten)]
+    pub story: Story,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum PackError {
+    Parse { format: &'static str, message: String },
+    NotAPack,
+    FromNewerVersion(u32),
+    NoUsableStories(Vec<String>),
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::Parse { format, message } => {
+                write!(f, "The file is not valid {} ({}).", format, message)
+            }
+            PackError::NotAPack => write!(
+                f,
+                "This file is not a story pack (expected format = \"{}\").",
+                PACK_FORMAT
+            ),
+            PackError::FromNewerVersion(v) => write!(
+                f,
+                "This story pack needs a newer version of the app (pack version {}).",
+                v
+            ),
+            PackError::NoUsableStories(problems) => write!(
+                f,
+                "None of the stories in this pack could be used: {}.",
+                problems.join("; ")
+            ),
+        }
+    }
+}
+
+impl StoryPack {
+    pub fn story_count(&self) -> usize {
+        self.stories.len()
+    }
+}
+
+// Parses and validates a pack file. TOML is chosen by the `.toml`
+// extension; anything else is read as JSON. Returns the cleaned pack and
+// one note per story that was repaired or left out.
+pub fn load_pack(text: &str, file_name: &str) -> Result<(StoryPack, Vec<String>), PackError> {
+    let pack: StoryPack = if file_name.to_lowercase().ends_with(".toml") {
+        toml::from_str(text).map_err(|e| PackError::Parse {
+            format: "TOML",
+            message: e.message().to_string(),
+        })?
+    } else {
+        serde_json::from_str(text).map_err(|e| PackError::Parse {
+            format: "JSON",
+            message: e.to_string(),
+        })?
+    };
+    if pack.format != PACK_FORMAT {
+        return Err(PackError::NotAPack);
+    }
+    if pack.version > PACK_VERSION {
+        return Err(PackError::FromNewerVersion(pack.version));
+    }
+    validate_pack(pack)
+}
+
+fn validate_pack(mut pack: StoryPack) -> Result<(StoryPack, Vec<String>), PackError> {
+    let mut notes = Vec::new();
+    let stories = std::mem::take(&mut pack.stories);
+    for (i, mut entry) in stories.into_iter().enumerate() {
+        let name = if entry.story.title.trim().is_empty() {
+            format!("story {}", i + 1)
+        } else {
+            format!("\"{}\"", entry.story.title.trim())
+        };
+        match entry.story.grade_level {
+            Some(grade) if grade <= crate::adaptive::MAX_GRADE => {}
+            _ => {
+                notes.push(format!("{} left out: grade_level must be 0–8", name));
+                continue;
+            }
+        }
+        match entry.story.validate() {
+            Ok((story, report)) => {
+                if !report.is_clean() {
+                    notes.push(format!("{} fixed: {}", name, report.summary()));
+                }
+                entry.story = story;
+                entry.tags = entry
+                    .tags
+                    .iter()
+                    .map(|t| t.trim().to_lowercase())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+                pack.stories.push(entry);
+            }
+            Err(report) => notes.push(format!("{} left out: {}", name, report.summary())),
+        }
+    }
+
+    if pack.stories.is_empty() {
+        return Err(PackError::NoUsableStories(notes));
+    }
+    Ok((pack, notes))
+}
+
+// A new, empty pack in the current format (the story editor starts here).
+pub fn new_pack(name: &str) -> StoryPack {
+    StoryPack {
+        format: PACK_FORMAT.to_string(),
+        version: PACK_VERSION,
+        name: name.to_string(),
+        author: String::new(),
+        license: String::new(),
+        description: String::new(),
+        stories: Vec::new(),
+    }
+}
+
+// Pack file text, readable by `load_pack` in either format.
+pub fn to_json(pack: &StoryPack) -> Result<String, String> {
+    serde_json::to_string_pretty(pack).map_err(|e| e.to_string())
+}
+
+pub fn to_toml(pack: &StoryPack) -> Result<String, String> {
+    toml::to_string(pack).map_err(|e| e.to_string())
+}
+
+// Adds a pack to the library, replacing an earlier import of the same pack
+// (same name and author) so re-importing an updated file doesn't duplicate.
+pub fn merge(library: &mut Vec<StoryPack>, pack: StoryPack) {
+    library.retain(|p| !(p.name == pack.name && p.author == pack.author));
+    library.push(pack);
+}
+
+// Saves one story into the library pack called `pack_name`, creating the
+// pack if needed and replacing a story with the same title.
+pub fn save_story(library: &mut Vec<StoryPack>, pack_name: &str, entry: PackStory) {
+    let index = match library
+        .iter()
+        .position(|p| p.name == pack_name && p.author.is_empty())
+    {
+        Some(i) => i,
+        None => {
+            library.push(new_pack(pack_name));
+            library.len() - 1
+        }
+    };
+    let stories = &mut library[index].stories;
+    match stories
+        .iter()
+        .position(|s| s.story.title.trim() == entry.story.title.trim())
+    {
+        Some(i) => stories[i] = entry,
+        None => stories.push(entry),
+    }
+}
+
+// Every imported story with its tags, ready to join the built-in fallback
+// stories.
+pub fn library_stories(library: &[StoryPack]) -> Vec<PackStory> {
+    library
+        .iter()
+        .flat_map(|p| p.stories.iter().cloned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACK_JSON: &str = r#"{
+        "format": "kids-story-pack",
+        "version": 1,
+        "name": "Ocean",
+        "author": "Ms. Lee",
+        "stories": [
+            {
+                "title": "The Crab",
+                "grade_level": 2,
+                "tags": [" Ocean ", ""],
+                "paragraphs": ["A crab walked on the sand."],
+                "questions": [
+                    {"text": "Where was the crab?", "paragraph_index": 0, "kind": "multiple_choice",
+                     "choices": ["On the sand", "In a tree"], "correct_index": 0}
+                ]
+            },
+            {
+                "title": "No Grade",
+                "paragraphs": ["A fish swam."],
+                "questions": [{"text": "Did it swim?", "paragraph_index": 0, "kind": "true_false", "answer": true}]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn loads_a_pack_and_leaves_out_unusable_stories() {
+        let (pack, notes) = load_pack(PACK_JSON, "ocean.json").unwrap();
+        assert_eq!(pack.story_count(), 1);
+        assert_eq!(pack.stories[0].tags, vec!["ocean"]);
+        assert_eq!(
+            notes,
+            vec!["\"No Grade\" left out: grade_level must be 0–8"]
+        );
+    }
+
+    #[test]
+    fn json_and_toml_round_trip() {
+        let (pack, _) = load_pack(PACK_JSON, "ocean.json").unwrap();
+        let json = to_json(&pack).unwrap();
+        assert_eq!(load_pack(&json, "ocean.json").unwrap().0, pack);
+        let toml = to_toml(&pack).unwrap();
+        assert_eq!(load_pack(&toml, "Ocean.TOML").unwrap().0, pack);
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_usable_packs() {
+        assert!(matches!(
+            load_pack("{", "a.json"),
+            Err(PackError::Parse { format: "JSON", .. })
+        ));
+        let other = PACK_JSON.replace("kids-story-pack", "recipes");
+        assert_eq!(load_pack(&other, "a.json"), Err(PackError::NotAPack));
+        let newer = PACK_JSON.replace("\"version\": 1", "\"version\": 9");
+        assert_eq!(
+            load_pack(&newer, "a.json"),
+            Err(PackError::FromNewerVersion(9))
+        );
+        let empty = PACK_JSON.replace("\"grade_level\": 2,", "");
+        assert!(matches!(
+            load_pack(&empty, "a.json"),
+            Err(PackError::NoUsableStories(_))
+        ));
+    }
+
+    #[test]
+    fn merge_replaces_the_same_pack() {
+        let (pack, _) = load_pack(PACK_JSON, "ocean.json").unwrap();
+        let mut library = vec![pack.clone(), new_pack("Space")];
+        merge(&mut library, pack);
+        assert_eq!(library.len(), 2);
+        assert_eq!(library[1].name, "Ocean");
+    }
+
+    #[test]
+    fn save_story_creates_the_pack_and_replaces_by_title() {
+        let (pack, _) = load_pack(PACK_JSON, "ocean.json").unwrap();
+        let mut entry = pack.stories[0].clone();
+        let mut library = Vec::new();
+        save_story(&mut library, EDITED_PACK_NAME, entry.clone());
+        entry.tags = vec!["beach".into()];
+        save_story(&mut library, EDITED_PACK_NAME, entry.clone());
+        assert_eq!(library.len(), 1);
+        assert_eq!(library[0].stories, vec![entry]);
+        assert_eq!(library_stories(&library).len(), 1);
+    }
+}
@@ -0,0 +1,122 @@
+// E:\rust_dev\kids_story_trainer\src\progress.rs
+//
+// Summaries over the saved session history for the progress dashboard.
+use crate::scoring::Accuracy;
+use crate::storage::SessionRecord;
+
+// A question type is "weak" when first-try accuracy is below this, once
+// there are enough questions of that type to judge.
+const WEAK_FIRST_TRY_PERCENT: i32 = 70;
+const MIN_QUESTIONS_TO_JUDGE: usize = 3;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SessionPoint {
+    pub title: String,
+    pub completed_at: f64,
+    pub score_percent: Option<i32>,
+    pub first_try_percent: i32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct KindStats {
+    pub label: &'static str,
+    pub questions: usize,
+    pub correct: usize,
+    pub first_try_correct: usize,
+}
+
+impl KindStats {
+    pub fn first_try_percent(&self) -> i32 {
+        if self.questions == 0 {
+            0
+        } else {
+            (self.first_try_correct as f32 * 100.0 / self.questions as f32).round() as i32
+        }
+    }
+
+    pub fn is_weak(&self) -> bool {
+        self.questions >= MIN_QUESTIONS_TO_JUDGE && self.first_try_percent() < WEAK_FIRST_TRY_PERCENT
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProgressSummary {
+    pub stories_read: usize,
+    pub average_score: Option<i32>,
+    pub first_try_percent: Option<i32>,
+    // Oldest first.
+    pub sessions: Vec<SessionPoint>,
+    // In the order each question type was first seen.
+    pub kinds: Vec<KindStats>,
+}
+
+impl ProgressSummary {
+    // Weakest first.
+    pub fn weak_kinds(&self) -> Vec<&KindStats> {
+        let mut weak: Vec<&KindStats> = self.kinds.iter().filter(|k| k.is_weak()).collect();
+        weak.sort_by_key(|k| k.first_try_percent());
+        weak
+    }
+}
+
+pub fn summarize(history: &[SessionRecord]) -> ProgressSummary {
+    let mut sessions: Vec<SessionPoint> = history
+        .iter()
+        .map(|rec| SessionPoint {
+            title: rec.story.title.clone(),
+            completed_at: rec.completed_at,
+            score_percent: rec.score_percent,
+            first_try_percent: Accuracy::from_progress(&rec.progress).first_try_percent(),
+        })
+        .collect();
+    sessions.sort_by(|a, b| a.completed_at.total_cmp(&b.completed_at));
+
+    let scores: Vec<i32> = history.iter().filter_map(|rec| rec.score_percent).collect();
+    let average_score = if scores.is_empty() {
+        None
+    } else {
+        Some((scores.iter().sum::<i32>() as f32 / scores.len() as f32).round() as i32)
+    };
+
+    let mut kinds: Vec<KindStats> = Vec::new();
+    let mut total = 0;
+    let mut first_try_total = 0;
+    for rec in history {
+        for (q, qp) in rec.story.questions.iter().zip(&rec.progress) {
+            let label = q.kind.label();
+            let idx = match kinds.iter().position(|k| k.label == label) {
+                Some(idx) => idx,
+                None => {
+                    kinds.push(KindStats {
+                        label,
+                        questions: 0,
+                        correct: 0,
+                        first_try_correct: 0,
+                    });
+                    kinds.len() - 1
+                }
+            };
+            let first_try = qp.is_correct && qp.attempts == 1;
+            let stats = &mut kinds[idx];
+            stats.questions += 1;
+            stats.correct += qp.is_correct as usize;
+            stats.first_try_correct += first_try as usize;
+            total += 1;
+            first_try_total += first_try as usize;
+        }
+    }
+
+    let first_try_percent = if total == 0 {
+        None
+    } else {
+        Some((first_try_total as f32 * 100.0 / total as f32).round() as i32)
+    };
+
+    ProgressSummary {
+        stories_read: history.len(),
+        average_score,
+        first_try_percent,
+        sessions,
+        kinds,
+    }
+}
@@ -0,0 +1,655 @@
+// E:\rust_dev\kids_story_trainer\src\provider.rs
+//
+// Where stories come from. Each backend implements `StoryProvider`; a
+// `ProviderChain` tries them in priority order and always ends with the
+// built-in library, so the child gets a story no matter what.
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use gloo_net::http::{Request, Response};
+use gloo_timers::callback::Timeout;
+use gloo_timers::future::TimeoutFuture;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::adaptive::DifficultyLevel;
+use crate::config::{AppConfig, AuthHeader, BackendProfile, ProviderKind};
+use crate::fallback::pick_fallback_story;
+use crate::pack::PackStory;
+use crate::stream;
+use crate::validate::ValidationReport;
+use crate::Story;
+
+pub type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct StoryRequest {
+    pub topic: String,
+    pub level: DifficultyLevel,
+    pub num_questions: u8,
+}
+
+impl StoryRequest {
+    // The JSON body our own Worker expects (see README).
+    fn worker_payload(&self) -> serde_json::Value {
+        serde_json::json!({
+            "topic": self.topic,
+            "gradeLevel": self.level.grade_level,
+            "numParagraphs": self.level.num_paragraphs,
+            "numQuestions": self.num_questions,
+            "questionDifficulty": self.level.question_difficulty.key()
+        })
+    }
+}
+
+// Shared between the app and an in-flight request. Cancelling aborts the
+// current fetch and tells the chain to stop; the app also cancels the old
+// token whenever a new story is requested, so a late reply can't overwrite
+// the newer one.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    cancelled: Rc<Cell<bool>>,
+    controller: Rc<RefCell<Option<web_sys::AbortController>>>,
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.cancelled.set(true);
+        if let Some(c) = self.controller.borrow_mut().take() {
+            c.abort();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
+    }
+
+    fn track(&self, controller: Option<web_sys::AbortController>) {
+        *self.controller.borrow_mut() = controller;
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ProviderError {
+    Build(String),
+    Network(String),
+    TimedOut { seconds: u32 },
+    Status(u16),
+    Parse(String),
+    Unusable(ValidationReport),
+    Cancelled,
+}
+
+impl ProviderError {
+    // Worth trying the same provider again: server hiccups and dropped
+    // connections. Timeouts are not retried, the kid has waited long enough.
+    fn is_retryable(&self) -> bool {
+        match self {
+            ProviderError::Network(_) => true,
+            ProviderError::Status(code) => *code >= 500,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderError::Build(e) => write!(f, "could not build request ({})", e),
+            ProviderError::Network(e) => write!(f, "could not reach server ({})", e),
+            ProviderError::TimedOut { seconds } => write!(f, "timed out after {} seconds", seconds),
+            ProviderError::Status(code) => write!(f, "request failed with status {}", code),
+            ProviderError::Parse(e) => write!(f, "response parse error ({})", e),
+            ProviderError::Unusable(report) => {
+                write!(f, "story could not be used ({})", report.summary())
+            }
+            ProviderError::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+pub trait StoryProvider {
+    // Shown in messages and the footer, e.g. "Cloudflare Worker AI".
+    fn label(&self) -> &str;
+
+    // False for the built-in library (no AI involved).
+    fn uses_ai(&self) -> bool {
+        true
+    }
+
+    fn generate<'a>(
+        &'a self,
+        request: &'a StoryRequest,
+        cancel: &'a CancelToken,
+        on_progress: &'a dyn Fn(&Story),
+    ) -> LocalBoxFuture<'a, Result<Story, ProviderError>>;
+}
+
+// --- Cloudflare Worker (our own JSON contract) -------------------------------
+
+pub struct WorkerProvider {
+    label: String,
+    endpoint: String,
+    timeout_ms: u32,
+    auth_header: Option<AuthHeader>,
+    stream: bool,
+}
+
+impl StoryProvider for WorkerProvider {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn generate<'a>(
+        &'a self,
+        request: &'a StoryRequest,
+        cancel: &'a CancelToken,
+        on_progress: &'a dyn Fn(&Story),
+    ) -> LocalBoxFuture<'a, Result<Story, ProviderError>> {
+        Box::pin(async move {
+            let mut payload = request.worker_payload();
+            if self.stream {
+                payload["stream"] = true.into();
+            }
+            let reply = post_json(
+                &self.endpoint,
+                &payload,
+                self.timeout_ms,
+                self.auth_header.as_ref(),
+                cancel,
+            )
+            .await?;
+            // A Worker that doesn't stream yet still answers with one blob.
+            let content_type = reply.resp.headers().get("content-type").unwrap_or_default();
+            if self.stream && !content_type.starts_with("application/json") {
+                stream::read_story(reply, cancel, on_progress).await
+            } else {
+                reply.json::<Story>(cancel).await
+            }
+        })
+    }
+}
+
+// --- OpenAI-compatible chat completions ---------------------------------------
+
+pub struct OpenAiChatProvider {
+    label: String,
+    endpoint: String,
+    model: String,
+    timeout_ms: u32,
+    auth_header: Option<AuthHeader>,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletion {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: String,
+}
+
+impl StoryProvider for OpenAiChatProvider {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn generate<'a>(
+        &'a self,
+        request: &'a StoryRequest,
+        cancel: &'a CancelToken,
+        _on_progress: &'a dyn Fn(&Story),
+    ) -> LocalBoxFuture<'a, Result<Story, ProviderError>> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "model": self.model,
+                "messages": chat_messages(request),
+                "response_format": { "type": "json_object" },
+                "temperature": 0.7
+            });
+            let reply = post_json(
+                &self.endpoint,
+                &body,
+                self.timeout_ms,
+                self.auth_header.as_ref(),
+                cancel,
+            )
+            .await?;
+            let completion = reply.json::<ChatCompletion>(cancel).await?;
+            let content = completion
+                .choices
+                .into_iter()
+                .next()
+                .map(|c| c.message.content)
+                .ok_or_else(|| ProviderError::Parse("no choices in reply".into()))?;
+            parse_story_text(&content)
+        })
+    }
+}
+
+// --- Ollama-style local server (/api/chat) ------------------------------------
+
+pub struct OllamaProvider {
+    label: String,
+    endpoint: String,
+    model: String,
+    timeout_ms: u32,
+    auth_header: Option<AuthHeader>,
+}
+
+#[derive(Deserialize)]
+struct OllamaReply {
+    message: ChatMessage,
+}
+
+impl StoryProvider for OllamaProvider {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn generate<'a>(
+        &'a self,
+        request: &'a StoryRequest,
+        cancel: &'a CancelToken,
+        _on_progress: &'a dyn Fn(&Story),
+    ) -> LocalBoxFuture<'a, Result<Story, ProviderError>> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "model": self.model,
+                "messages": chat_messages(request),
+                "format": "json",
+                "stream": false
+            });
+            let reply = post_json(
+                &self.endpoint,
+                &body,
+                self.timeout_ms,
+                self.auth_header.as_ref(),
+                cancel,
+            )
+            .await?;
+            let reply = reply.json::<OllamaReply>(cancel).await?;
+            parse_story_text(&reply.message.content)
+        })
+    }
+}
+
+// --- Built-in library ---------------------------------------------------------
+
+// The stories compiled into the app plus any imported story packs.
+pub struct BuiltinProvider {
+    imported: Vec<PackStory>,
+}
+
+impl StoryProvider for BuiltinProvider {
+    fn label(&self) -> &str {
+        "Built-in stories"
+    }
+
+    fn uses_ai(&self) -> bool {
+        false
+    }
+
+    fn generate<'a>(
+        &'a self,
+        request: &'a StoryRequest,
+        _cancel: &'a CancelToken,
+        _on_progress: &'a dyn Fn(&Story),
+    ) -> LocalBoxFuture<'a, Result<Story, ProviderError>> {
+        Box::pin(async move {
+            Ok(pick_fallback_story(
+                &request.topic,
+                &self.imported,
+                request.level.grade_level,
+                request.level.num_paragraphs,
+                request.num_questions,
+            ))
+        })
+    }
+}
+
+// --- Chain ----------------------------------------------------------------------
+
+pub struct ChainResult {
+    pub story: Story,
+    pub used_ai: bool,
+    pub source: String,
+    // Problems worth telling the grown-up about (failed providers, repairs).
+    pub notes: Vec<String>,
+}
+
+// How often to re-try one provider before moving down the chain.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+struct RetryPolicy {
+    max_retries: u32,
+    backoff_ms: u32,
+}
+
+impl RetryPolicy {
+    fn delay_ms(self, retry: u32) -> u32 {
+        self.backoff_ms.saturating_mul(1 << retry.min(16))
+    }
+}
+
+pub struct ProviderChain {
+    providers: Vec<(Box<dyn StoryProvider>, RetryPolicy)>,
+}
+
+impl ProviderChain {
+    // The chosen profile first, then its `fallback_profiles` in order, then
+    // the built-in library (with `imported` stories from story packs).
+    pub fn from_config(
+        config: &AppConfig,
+        profile_name: &str,
+        imported: Vec<PackStory>,
+    ) -> ProviderChain {
+        let first = config.profile(profile_name);
+        let mut providers: Vec<(Box<dyn StoryProvider>, RetryPolicy)> = Vec::new();
+        let mut seen: Vec<String> = Vec::new();
+
+        let rest = first
+            .fallback_profiles
+            .iter()
+            .filter_map(|name| config.profiles.iter().find(|p| &p.name == name).cloned());
+        for profile in std::iter::once(first.clone()).chain(rest) {
+            if seen.contains(&profile.name) {
+                continue;
+            }
+            seen.push(profile.name.clone());
+            if let Some(provider) = provider_for(&profile) {
+                let retry = RetryPolicy {
+                    max_retries: profile.max_retries,
+                    backoff_ms: profile.retry_backoff_ms,
+                };
+                providers.push((provider, retry));
+            }
+        }
+        providers.push((Box::new(BuiltinProvider { imported }), RetryPolicy::default()));
+        ProviderChain { providers }
+    }
+
+    // None when the request was cancelled; the caller should leave the
+    // screen alone.
+    pub async fn generate(
+        &self,
+        request: &StoryRequest,
+        cancel: &CancelToken,
+        on_progress: &dyn Fn(&Story),
+    ) -> Option<ChainResult> {
+        let mut notes = Vec::new();
+
+        for (provider, retry) in &self.providers {
+            let result = generate_with_retry(provider.as_ref(), *retry, request, cancel, on_progress)
+                .await
+                .and_then(|story| {
+                    if provider.uses_ai() {
+                        story.validate().map_err(ProviderError::Unusable)
+                    } else {
+                        Ok((story, ValidationReport::default()))
+                    }
+                });
+            if cancel.is_cancelled() {
+                return None;
+            }
+
+            match result {
+                Ok((story, report)) => {
+                    if report.dropped_questions() > 0 {
+                        notes.push(format!(
+                            "{} story had problems we fixed ({}); {} question(s) removed",
+                            provider.label(),
+                            report.summary(),
+                            report.dropped_questions()
+                        ));
+                    } else if !report.is_clean() {
+                        notes.push(format!(
+                            "{} story had small problems we fixed ({})",
+                            provider.label(),
+                            report.summary()
+                        ));
+                    }
+                    return Some(ChainResult {
+                        story,
+                        used_ai: provider.uses_ai(),
+                        source: provider.label().to_string(),
+                        notes,
+                    });
+                }
+                Err(e) => notes.push(format!("{}: {}", provider.label(), e)),
+            }
+        }
+
+        // Unreachable in practice: the built-in provider never fails.
+        Some(ChainResult {
+            story: pick_fallback_story(
+                &request.topic,
+                &[],
+                request.level.grade_level,
+                request.level.num_paragraphs,
+                request.num_questions,
+            ),
+            used_ai: false,
+            source: "Built-in stories".to_string(),
+            notes,
+        })
+    }
+}
+
+// Re-tries 5xx and network failures with doubling waits, giving up early
+// if the request is cancelled while waiting.
+async fn generate_with_retry(
+    provider: &dyn StoryProvider,
+    retry: RetryPolicy,
+    request: &StoryRequest,
+    cancel: &CancelToken,
+    on_progress: &dyn Fn(&Story),
+) -> Result<Story, ProviderError> {
+    let mut attempt = 0;
+    loop {
+        match provider.generate(request, cancel, on_progress).await {
+            Err(e) if e.is_retryable() && attempt < retry.max_retries => {
+                TimeoutFuture::new(retry.delay_ms(attempt)).await;
+                if cancel.is_cancelled() {
+                    return Err(ProviderError::Cancelled);
+                }
+                attempt += 1;
+            }
+            other => return other,
+        }
+    }
+}
+
+fn provider_for(profile: &BackendProfile) -> Option<Box<dyn StoryProvider>> {
+    let endpoint = profile.endpoint.clone()?;
+    let label = profile.display_name().to_string();
+    let model = profile.model.clone().unwrap_or_default();
+    let provider: Box<dyn StoryProvider> = match profile.kind {
+        ProviderKind::Builtin => return None,
+        ProviderKind::Worker => Box::new(WorkerProvider {
+            label,
+            endpoint,
+            timeout_ms: profile.timeout_ms,
+            auth_header: profile.auth_header.clone(),
+            stream: profile.stream,
+        }),
+        ProviderKind::OpenAiChat => Box::new(OpenAiChatProvider {
+            label,
+            endpoint,
+            model,
+            timeout_ms: profile.timeout_ms,
+            auth_header: profile.auth_header.clone(),
+        }),
+        ProviderKind::Ollama => Box::new(OllamaProvider {
+            label,
+            endpoint,
+            model,
+            timeout_ms: profile.timeout_ms,
+            auth_header: profile.auth_header.clone(),
+        }),
+    };
+    Some(provider)
+}
+
+// --- Shared helpers -------------------------------------------------------------
+
+// Aborts a request after `timeout_ms` without progress. Kept alive with the
+// response, so a server that sends headers and then stalls still times out
+// while the body is read.
+struct RequestTimer {
+    controller: Option<web_sys::AbortController>,
+    timed_out: Rc<Cell<bool>>,
+    timeout_ms: u32,
+    timeout: RefCell<Option<Timeout>>,
+}
+
+impl RequestTimer {
+    fn start(controller: Option<web_sys::AbortController>, timeout_ms: u32) -> RequestTimer {
+        let timer = RequestTimer {
+            controller,
+            timed_out: Rc::new(Cell::new(false)),
+            timeout_ms,
+            timeout: RefCell::new(None),
+        };
+        timer.restart();
+        timer
+    }
+
+    // Starts the wait over (replacing the old timeout cancels it).
+    fn restart(&self) {
+        let controller = self.controller.clone();
+        let timed_out = self.timed_out.clone();
+        *self.timeout.borrow_mut() = Some(Timeout::new(self.timeout_ms, move || {
+            timed_out.set(true);
+            if let Some(c) = controller {
+                c.abort();
+            }
+        }));
+    }
+
+    // What a failed fetch or body read really was: a cancel, our timeout,
+    // or `other`.
+    fn error(&self, cancel: &CancelToken, other: ProviderError) -> ProviderError {
+        if cancel.is_cancelled() {
+            ProviderError::Cancelled
+        } else if self.timed_out.get() {
+            // Rounded up so a short timeout never reads "0 seconds".
+            ProviderError::TimedOut {
+                seconds: self.timeout_ms.div_ceil(1000),
+            }
+        } else {
+            other
+        }
+    }
+}
+
+// A 200 response whose body has not been read yet.
+pub struct Reply {
+    pub resp: Response,
+    timer: RequestTimer,
+}
+
+impl Reply {
+    // The server is still sending: give it another `timeout_ms`.
+    pub fn keep_alive(&self) {
+        self.timer.restart();
+    }
+
+    pub fn error(&self, cancel: &CancelToken, other: ProviderError) -> ProviderError {
+        self.timer.error(cancel, other)
+    }
+
+    async fn json<T: DeserializeOwned>(&self, cancel: &CancelToken) -> Result<T, ProviderError> {
+        self.resp
+            .json::<T>()
+            .await
+            .map_err(|e| self.error(cancel, ProviderError::Parse(e.to_string())))
+    }
+}
+
+// POSTs JSON, aborting after `timeout_ms` without progress or when `cancel`
+// fires. Only 200 responses are Ok; the timeout keeps running until the
+// returned reply is dropped.
+async fn post_json(
+    url: &str,
+    body: &serde_json::Value,
+    timeout_ms: u32,
+    auth_header: Option<&AuthHeader>,
+    cancel: &CancelToken,
+) -> Result<Reply, ProviderError> {
+    if cancel.is_cancelled() {
+        return Err(ProviderError::Cancelled);
+    }
+    let controller = web_sys::AbortController::new().ok();
+    cancel.track(controller.clone());
+    let timer = RequestTimer::start(controller.clone(), timeout_ms);
+    let signal = controller.as_ref().map(|c| c.signal());
+
+    let mut builder = Request::post(url).abort_signal(signal.as_ref());
+    if let Some(auth) = auth_header {
+        builder = builder.header(&auth.name, &auth.value);
+    }
+    let req = builder
+        .json(body)
+        .map_err(|e| ProviderError::Build(e.to_string()))?;
+
+    // The controller stays tracked so cancelling also stops a streamed body.
+    match req.send().await {
+        Ok(resp) if resp.status() == 200 => Ok(Reply { resp, timer }),
+        Ok(resp) => Err(ProviderError::Status(resp.status())),
+        Err(e) => Err(timer.error(cancel, ProviderError::Network(e.to_string()))),
+    }
+}
+
+// Chat models sometimes wrap JSON in ```json fences or add a sentence
+// before it; take the outermost {...} block.
+fn parse_story_text(content: &str) -> Result<Story, ProviderError> {
+    let start = content.find('{');
+    let end = content.rfind('}');
+    let json = match (start, end) {
+        (Some(s), Some(e)) if e > s => &content[s..=e],
+        _ => return Err(ProviderError::Parse("no JSON object in reply".into())),
+    };
+    serde_json::from_str(json).map_err(|e| ProviderError::Parse(e.to_string()))
+}
+
+// System + user prompt for chat-style models, describing the same JSON
+// contract the Worker returns.
+fn chat_messages(request: &StoryRequest) -> serde_json::Value {
+    let grade = if request.level.grade_level == 0 {
+        "kindergarten".to_string()
+    } else {
+        format!("grade {}", request.level.grade_level)
+    };
+    let system = "You write short, kind, age-appropriate reading passages for children \
+        and comprehension questions about them. Reply with ONE JSON object only, no prose. \
+        Shape: {\"title\": string, \"paragraphs\": [string], \"questions\": [question]}. \
+        Each question has \"text\", \"paragraph_index\" (0-based paragraph it is about) and \"kind\", \
+        one of: \"multiple_choice\" (\"choices\": [string], \"correct_index\": number), \
+        \"true_false\" (\"answer\": bool), \
+        \"short_answer\" (\"accepted_answers\": [string]), \
+        \"evidence_select\" (\"correct_sentence\": 0-based sentence in that paragraph), \
+        \"sequence\" (\"events\": [string] listed scrambled, \"correct_order\": [indexes of events first to last]), \
+        \"vocabulary\" (\"word\": a word from that paragraph, \"choices\": [string], \"correct_index\": number).";
+    let user = format!(
+        "Topic: {}. Reading level: {}. Write {} paragraph(s) and {} question(s). \
+         Question difficulty: {}. Use a mix of question kinds.",
+        request.topic,
+        grade,
+        request.level.num_paragraphs,
+        request.num_questions,
+        request.level.question_difficulty.key()
+    );
+    serde_json::json!([
+        { "role": "system", "content": system },
+        { "role": "user", "content": user }
+    ])
+}
@@ -0,0 +1,226 @@
+// E:\rust_dev\kids_story_trainer\src\quiz.rs
+//
+// The question-by-question quiz: the kid's answer so far, checking it,
+// skipping and moving on. The app and the story editor's student preview
+// both use `use_quiz`, so a preview grades exactly like the real thing;
+// what happens after the last question is up to the caller.
+use web_sys::{Event, HtmlInputElement};
+use yew::prelude::*;
+
+use crate::{
+    current_sequence_order, fresh_progress, grade_answer, missing_answer_message, QuestionProgress,
+    Story,
+};
+
+#[derive(Clone)]
+pub struct Quiz {
+    pub progress: UseStateHandle<Vec<QuestionProgress>>,
+    pub current_question: UseStateHandle<usize>,
+    pub selected_choice: UseStateHandle<Option<usize>>,
+    pub typed_answer: UseStateHandle<String>,
+    // Current arrangement of a sequence question's events (indices into
+    // `events`); empty until the kid first moves something.
+    pub sequence_order: UseStateHandle<Vec<usize>>,
+    pub on_choice_change: Callback<Event>,
+    pub on_sentence_click: Callback<usize>,
+    pub on_move_event: Callback<(usize, usize)>,
+    pub on_answer_input: Callback<InputEvent>,
+    pub on_check_answer: Callback<MouseEvent>,
+    pub on_skip_question: Callback<MouseEvent>,
+}
+
+impl Quiz {
+    // Fresh progress for `story`, back at its first question.
+    pub fn reset(&self, story: Option<&Story>) {
+        self.progress.set(fresh_progress(story));
+        self.current_question.set(0);
+        self.clear_answer();
+    }
+
+    fn clear_answer(&self) {
+        self.selected_choice.set(None);
+        self.typed_answer.set(String::new());
+        self.sequence_order.set(Vec::new());
+    }
+
+    // True once the current question is answered correctly or skipped.
+    pub fn current_done(&self) -> bool {
+        self.progress
+            .get(*self.current_question)
+            .map(|qp| qp.is_correct || qp.skipped)
+            .unwrap_or(false)
+    }
+}
+
+// Quiz state for whatever `story` holds. `on_message` gets prompts such as
+// "Please choose an answer" (None clears them); `on_finished` gets the
+// progress when the last question is done.
+#[hook]
+pub fn use_quiz(
+    story: &UseStateHandle<Option<Story>>,
+    on_message: Callback<Option<String>>,
+    on_finished: Callback<Vec<QuestionProgress>>,
+) -> Quiz {
+    let progress = {
+        let story = story.clone();
+        use_state(move || fresh_progress(story.as_ref()))
+    };
+    let current_question = use_state(|| 0usize);
+    let selected_choice = use_state(|| Option::<usize>::None);
+    let typed_answer = use_state(String::new);
+    let sequence_order = use_state(Vec::<usize>::new);
+
+    // Handle radio choice selection
+    let on_choice_change = {
+        let selected_choice = selected_choice.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(idx) = input.value().parse::<usize>() {
+                selected_choice.set(Some(idx));
+            }
+        })
+    };
+
+    // Handle tapping a sentence in the highlighted paragraph (evidence questions)
+    let on_sentence_click = {
+        let selected_choice = selected_choice.clone();
+        Callback::from(move |idx: usize| selected_choice.set(Some(idx)))
+    };
+
+    // Move a sequence event from one position to another (tap arrows or drag)
+    let on_move_event = {
+        let story = story.clone();
+        let current_question = current_question.clone();
+        let sequence_order = sequence_order.clone();
+        Callback::from(move |(from, to): (usize, usize)| {
+            let Some(q) = (*story)
+                .as_ref()
+                .and_then(|st| st.questions.get(*current_question))
+            else {
+                return;
+            };
+            let mut order = current_sequence_order(q, &sequence_order);
+            if from >= order.len() || to >= order.len() || from == to {
+                return;
+            }
+            let event = order.remove(from);
+            order.insert(to, event);
+            sequence_order.set(order);
+        })
+    };
+
+    // Handle typing into a short-answer box
+    let on_answer_input = {
+        let typed_answer = typed_answer.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            typed_answer.set(input.value());
+        })
+    };
+
+    let quiz = Quiz {
+        progress,
+        current_question,
+        selected_choice,
+        typed_answer,
+        sequence_order,
+        on_choice_change,
+        on_sentence_click,
+        on_move_event,
+        on_answer_input,
+        on_check_answer: Callback::noop(),
+        on_skip_question: Callback::noop(),
+    };
+
+    // Stores the progress and, once question `idx` is done, moves linearly
+    // to the next question or finishes.
+    let save_and_advance = {
+        let quiz = quiz.clone();
+        move |idx: usize, qp_vec: Vec<QuestionProgress>, total: usize| {
+            let done = qp_vec
+                .get(idx)
+                .map(|qp| qp.is_correct || qp.skipped)
+                .unwrap_or(false);
+            quiz.progress.set(qp_vec.clone());
+            if !done {
+                return;
+            }
+            if idx + 1 < total {
+                quiz.current_question.set(idx + 1);
+                quiz.clear_answer();
+            } else {
+                on_finished.emit(qp_vec);
+            }
+        }
+    };
+
+    // Check current answer; wrong answers count an attempt and stay put
+    let on_check_answer = {
+        let story = story.clone();
+        let quiz = quiz.clone();
+        let on_message = on_message.clone();
+        let save_and_advance = save_and_advance.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_message.emit(None);
+            let Some(st) = (*story).as_ref() else {
+                on_message.emit(Some("No story is loaded yet.".into()));
+                return;
+            };
+            let idx = *quiz.current_question;
+            let (Some(q), Some(current)) = (st.questions.get(idx), quiz.progress.get(idx)) else {
+                return;
+            };
+            // If this question is already done, ignore further clicks.
+            if current.is_correct || current.skipped {
+                return;
+            }
+            // Don't count attempts if nothing has been answered yet
+            if let Some(msg) = missing_answer_message(
+                q,
+                *quiz.selected_choice,
+                &quiz.typed_answer,
+                &quiz.sequence_order,
+            ) {
+                on_message.emit(Some(msg.into()));
+                return;
+            }
+
+            let order = current_sequence_order(q, &quiz.sequence_order);
+            let credit = grade_answer(q, *quiz.selected_choice, &quiz.typed_answer, &order);
+            let mut qp_vec = (*quiz.progress).clone();
+            let qp = &mut qp_vec[idx];
+            qp.attempts += 1;
+            qp.credit = qp.credit.max(credit);
+            if qp.attempts == 1 {
+                qp.first_try_credit = credit;
+            }
+            qp.is_correct = credit >= 1.0;
+            save_and_advance(idx, qp_vec, st.questions.len());
+        })
+    };
+
+    // Skip question (keeps any partial credit)
+    let on_skip_question = {
+        let story = story.clone();
+        let quiz = quiz.clone();
+        Callback::from(move |_: MouseEvent| {
+            let total = (*story).as_ref().map(|st| st.questions.len()).unwrap_or(0);
+            let idx = *quiz.current_question;
+            let mut qp_vec = (*quiz.progress).clone();
+            let Some(qp) = qp_vec.get_mut(idx) else {
+                return;
+            };
+            if qp.is_correct || qp.skipped {
+                return;
+            }
+            qp.skipped = true;
+            save_and_advance(idx, qp_vec, total);
+        })
+    };
+
+    Quiz {
+        on_check_answer,
+        on_skip_question,
+        ..quiz
+    }
+}
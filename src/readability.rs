@@ -0,0 +1,264 @@
+// E:\rust_dev\kids_story_trainer\src\readability.rs
+//
+// How hard a passage actually reads. Combines the Flesch-Kincaid grade
+// (sentence length + syllables per word) with how many words fall outside
+// a list of very common early-reader words, and turns that into a rough
+// Lexile-style number for grown-ups who think in those terms. Estimates
+// only: real Lexile measures use a licensed corpus.
+use crate::{split_sentences, Story};
+
+// A story reading this many grades away from the request is "far off".
+pub const FAR_OFF_GRADES: f32 = 2.0;
+// How many times to ask the AI again for a far-off story before keeping it.
+pub const MAX_REGENERATIONS: u32 = 1;
+
+// Weight of Flesch-Kincaid in the combined estimate; the rest is vocabulary.
+const FK_WEIGHT: f32 = 0.7;
+// Each this-many percent of uncommon words adds one grade of vocabulary.
+const RARE_PERCENT_PER_GRADE: f32 = 10.0;
+
+// Dolch sight words plus the most frequent Fry words: if a word is here, a
+// first or second grader has almost certainly seen it.
+const COMMON_WORDS: &[&str] = &[
+    "a", "about", "after", "again", "all", "always", "am", "an", "and", "any", "are", "around",
+    "as", "ask", "at", "ate", "away", "back", "be", "because", "been", "before", "best",
+    "better", "big", "black", "blue", "both", "boy", "bring", "brown", "but", "buy", "by",
+    "call", "came", "can", "carry", "clean", "cold", "come", "could", "cut", "day", "did", "do",
+    "does", "dog", "done", "down", "draw", "drink", "each", "eat", "eight", "end", "even",
+    "every", "fall", "far", "fast", "find", "first", "five", "fly", "for", "found", "four",
+    "friend", "from", "full", "funny", "gave", "get", "girl", "give", "go", "going", "good",
+    "got", "great", "green", "grow", "had", "happy", "has", "have", "he", "help", "her", "here",
+    "him", "his", "home", "hot", "house", "how", "hurt", "i", "if", "in", "into", "is", "it",
+    "its", "jump", "just", "keep", "kind", "know", "laugh", "let", "light", "like", "little",
+    "live", "long", "look", "made", "make", "many", "may", "me", "mom", "more", "much", "must",
+    "my", "myself", "never", "new", "next", "night", "no", "not", "now", "of", "off", "old",
+    "on", "once", "one", "only", "open", "or", "other", "our", "out", "over", "own", "people",
+    "pick", "place", "play", "please", "pretty", "pull", "put", "ran", "read", "red", "ride",
+    "right", "round", "run", "said", "saw", "say", "school", "see", "seven", "shall", "she",
+    "show", "sing", "sit", "six", "sleep", "small", "so", "some", "soon", "start", "stop",
+    "sun", "take", "tell", "ten", "than", "thank", "that", "the", "their", "them", "then",
+    "there", "these", "they", "thing", "think", "this", "those", "three", "time", "to",
+    "today", "together", "too", "tree", "try", "two", "under", "up", "upon", "us", "use",
+    "very", "walk", "want", "warm", "was", "wash", "water", "way", "we", "well", "went",
+    "were", "what", "when", "where", "which", "white", "who", "why", "will", "wish", "with",
+    "work", "would", "write", "yellow", "yes", "you", "your",
+];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Readability {
+    pub words: usize,
+    pub sentences: usize,
+    pub syllables: usize,
+    pub words_per_sentence: f32,
+    pub syllables_per_word: f32,
+    pub flesch_kincaid_grade: f32,
+    // Percent of words not on the common-word list.
+    pub rare_word_percent: f32,
+    // Combined grade estimate (FK + vocabulary), clamped to 0..=12.
+    pub grade_estimate: f32,
+}
+
+impl Readability {
+    // Rough Lexile-style measure: about 100L per grade, starting near 200L
+    // in kindergarten.
+    pub fn lexile_estimate(&self) -> i32 {
+        ((200.0 + self.grade_estimate * 100.0) / 10.0).round() as i32 * 10
+    }
+
+    // "K", "3", "12+" – the estimate rounded for display.
+    pub fn grade_label(&self) -> String {
+        let grade = self.grade_estimate.round() as u8;
+        if grade >= 12 {
+            "12+".into()
+        } else {
+            crate::adaptive::grade_label(grade)
+        }
+    }
+
+    pub fn is_far_off(&self, requested_grade: u8) -> bool {
+        (self.grade_estimate - requested_grade as f32).abs() > FAR_OFF_GRADES
+    }
+
+    // One line for tooltips and messages.
+    pub fn summary(&self) -> String {
+        format!(
+            "Flesch-Kincaid grade {:.1}, {:.1} words per sentence, {:.2} syllables per word, {:.0}% uncommon words",
+            self.flesch_kincaid_grade,
+            self.words_per_sentence,
+            self.syllables_per_word,
+            self.rare_word_percent
+        )
+    }
+}
+
+// Analyzes the story's paragraphs (not the questions). None for a story
+// with no words, e.g. a stream that has only sent the title.
+pub fn analyze(story: &Story) -> Option<Readability> {
+    let mut sentences = 0;
+    let mut words = 0;
+    let mut syllables = 0;
+    let mut rare = 0;
+
+    for paragraph in &story.paragraphs {
+        for sentence in split_sentences(paragraph) {
+            let mut in_sentence = 0;
+            for word in sentence_words(&sentence) {
+                in_sentence += 1;
+                syllables += count_syllables(&word);
+                if !is_common(&word) {
+                    rare += 1;
+                }
+            }
+            if in_sentence > 0 {
+                sentences += 1;
+                words += in_sentence;
+            }
+        }
+    }
+    if words == 0 {
+        return None;
+    }
+
+    let words_per_sentence = words as f32 / sentences as f32;
+    let syllables_per_word = syllables as f32 / words as f32;
+    let flesch_kincaid_grade = 0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59;
+    let rare_word_percent = rare as f32 * 100.0 / words as f32;
+    let vocabulary_grade = rare_word_percent / RARE_PERCENT_PER_GRADE;
+    let grade_estimate = (FK_WEIGHT * flesch_kincaid_grade + (1.0 - FK_WEIGHT) * vocabulary_grade)
+        .clamp(0.0, 12.0);
+
+    Some(Readability {
+        words,
+        sentences,
+        syllables,
+        words_per_sentence,
+        syllables_per_word,
+        flesch_kincaid_grade,
+        rare_word_percent,
+        grade_estimate,
+    })
+}
+
+// Lowercase words, apostrophes kept ("don't"), numbers dropped.
+fn sentence_words(sentence: &str) -> impl Iterator<Item = String> + '_ {
+    sentence
+        .split(|c: char| !(c.is_alphabetic() || c == '\'' || c == '’'))
+        .map(|w| w.trim_matches(|c| c == '\'' || c == '’').to_lowercase())
+        .filter(|w| !w.is_empty())
+}
+
+// Plurals and simple endings of common words count as common.
+fn is_common(word: &str) -> bool {
+    let base = word.trim_end_matches("'s").trim_end_matches("’s");
+    let candidates = [
+        base,
+        base.strip_suffix('s').unwrap_or(base),
+        base.strip_suffix("es").unwrap_or(base),
+        base.strip_suffix("ed").unwrap_or(base),
+        base.strip_suffix("ing").unwrap_or(base),
+        base.strip_suffix("ly").unwrap_or(base),
+    ];
+    candidates.iter().any(|c| COMMON_WORDS.contains(c))
+}
+
+// Vowel-group count with the usual English adjustments (silent final "e",
+// "-le" endings, "-ed" that isn't its own syllable). Always at least 1.
+pub fn count_syllables(word: &str) -> usize {
+    let word: String = word
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .collect();
+    if word.is_empty() {
+        return 0;
+    }
+    if word.len() <= 3 {
+        return 1;
+    }
+
+    let is_vowel = |c: char| "aeiouy".contains(c);
+    let chars: Vec<char> = word.chars().collect();
+    let mut count = 0;
+    let mut prev_vowel = false;
+    for &c in &chars {
+        let vowel = is_vowel(c);
+        if vowel && !prev_vowel {
+            count += 1;
+        }
+        prev_vowel = vowel;
+    }
+
+    // Silent final "e" ("make"), but "table" and "free" keep theirs.
+    let n = chars.len();
+    if word.ends_with('e') && !word.ends_with("le") && !word.ends_with("ee") && count > 1 {
+        count -= 1;
+    }
+    if word.ends_with("ed") && n > 3 && !matches!(chars[n - 3], 't' | 'd') && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn story(paragraphs: &[&str]) -> Story {
+        Story {
+            paragraphs: paragraphs.iter().map(|p| p.to_string()).collect(),
+            ..Story::default()
+        }
+    }
+
+    #[test]
+    fn counts_syllables() {
+        for (word, expected) in [
+            ("cat", 1),
+            ("make", 1),
+            ("table", 2),
+            ("jumped", 1),
+            ("wanted", 2),
+            ("butterfly", 3),
+            ("free", 1),
+            ("", 0),
+        ] {
+            assert_eq!(count_syllables(word), expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn simple_text_reads_at_an_early_grade() {
+        let r = analyze(&story(&["The dog ran. The dog is big. I like the dog."])).unwrap();
+        assert_eq!((r.words, r.sentences), (11, 3));
+        assert_eq!(r.rare_word_percent, 0.0);
+        assert_eq!(r.grade_label(), "K");
+        assert!(r.is_far_off(5));
+        assert!(!r.is_far_off(1));
+    }
+
+    #[test]
+    fn long_sentences_and_rare_words_read_harder() {
+        let easy = analyze(&story(&["The dog ran home. It was a good day."])).unwrap();
+        let hard = analyze(&story(&[
+            "Paleontologists carefully examined the enormous fossilized skeleton, \
+             comparing its vertebrae with illustrations in their reference encyclopedias.",
+        ]))
+        .unwrap();
+        assert!(hard.grade_estimate > easy.grade_estimate + 5.0);
+        assert!(hard.lexile_estimate() > easy.lexile_estimate());
+    }
+
+    #[test]
+    fn endings_of_common_words_count_as_common() {
+        assert!(is_common("dogs"));
+        assert!(is_common("jumped"));
+        assert!(is_common("playing"));
+        assert!(!is_common("volcano"));
+    }
+
+    #[test]
+    fn no_words_means_no_estimate() {
+        assert_eq!(analyze(&story(&[])), None);
+        assert_eq!(analyze(&story(&["123 456."])), None);
+    }
+}
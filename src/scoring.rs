@@ -0,0 +1,196 @@
+// E:\rust_dev\kids_story_trainer\src\scoring.rs
+use serde::{Deserialize, Serialize};
+
+use crate::QuestionProgress;
+
+// How attempts and skips turn into points. Chosen in settings.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringPolicy {
+    // Full credit whenever the question is eventually answered correctly;
+    // partial credit is kept when moving on.
+    #[default]
+    Lenient,
+    // Credit only for what was right on the first attempt.
+    FirstTry,
+    // Each extra attempt takes 25% off, down to a 25% floor. Skips are 0.
+    Decaying,
+}
+
+impl ScoringPolicy {
+    pub const ALL: [ScoringPolicy; 3] = [
+        ScoringPolicy::Lenient,
+        ScoringPolicy::FirstTry,
+        ScoringPolicy::Decaying,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            ScoringPolicy::Lenient => "lenient",
+            ScoringPolicy::FirstTry => "first_try",
+            ScoringPolicy::Decaying => "decaying",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<ScoringPolicy> {
+        ScoringPolicy::ALL.into_iter().find(|p| p.key() == key)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScoringPolicy::Lenient => "Full credit once correct",
+            ScoringPolicy::FirstTry => "First try only",
+            ScoringPolicy::Decaying => "Less credit for each extra try",
+        }
+    }
+
+    // Credit for one question, 0.0..=1.0.
+    pub fn question_credit(self, qp: &QuestionProgress) -> f32 {
+        match self {
+            ScoringPolicy::Lenient => qp.credit,
+            ScoringPolicy::FirstTry => qp.first_try_credit,
+            ScoringPolicy::Decaying => {
+                if qp.skipped && qp.credit < 1.0 {
+                    return 0.0;
+                }
+                let extra_attempts = qp.attempts.saturating_sub(1) as f32;
+                let factor = (1.0 - 0.25 * extra_attempts).max(0.25);
+                qp.credit * factor
+            }
+        }
+    }
+}
+
+// Score out of 100 for a finished (or in-progress) quiz, rounded.
+pub fn score_percent(progress: &[QuestionProgress], policy: ScoringPolicy) -> Option<i32> {
+    if progress.is_empty() {
+        return None;
+    }
+    let points_per = 100.0 / progress.len() as f32;
+    let score: f32 = progress
+        .iter()
+        .map(|qp| points_per * policy.question_credit(qp))
+        .sum();
+    Some(score.round() as i32)
+}
+
+// Raw and first-try accuracy, shown side by side on the results screen.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Accuracy {
+    pub correct: usize,
+    pub first_try_correct: usize,
+    pub total: usize,
+}
+
+impl Accuracy {
+    pub fn from_progress(progress: &[QuestionProgress]) -> Accuracy {
+        Accuracy {
+            correct: progress.iter().filter(|qp| qp.is_correct).count(),
+            first_try_correct: progress
+                .iter()
+                .filter(|qp| qp.is_correct && qp.attempts == 1)
+                .count(),
+            total: progress.len(),
+        }
+    }
+
+    pub fn percent(self) -> i32 {
+        percent_of(self.correct, self.total)
+    }
+
+    pub fn first_try_percent(self) -> i32 {
+        percent_of(self.first_try_correct, self.total)
+    }
+}
+
+fn percent_of(part: usize, total: usize) -> i32 {
+    if total == 0 {
+        0
+    } else {
+        (part as f32 * 100.0 / total as f32).round() as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(
+        attempts: u32,
+        is_correct: bool,
+        skipped: bool,
+        credit: f32,
+        first_try_credit: f32,
+    ) -> QuestionProgress {
+        QuestionProgress {
+            attempts,
+            is_correct,
+            skipped,
+            credit,
+            first_try_credit,
+        }
+    }
+
+    fn first_try() -> QuestionProgress {
+        progress(1, true, false, 1.0, 1.0)
+    }
+
+    fn third_try() -> QuestionProgress {
+        progress(3, true, false, 1.0, 0.0)
+    }
+
+    fn skipped() -> QuestionProgress {
+        progress(0, false, true, 0.0, 0.0)
+    }
+
+    #[test]
+    fn keys_round_trip() {
+        for policy in ScoringPolicy::ALL {
+            assert_eq!(ScoringPolicy::from_key(policy.key()), Some(policy));
+        }
+        assert_eq!(ScoringPolicy::from_key("nope"), None);
+    }
+
+    #[test]
+    fn lenient_gives_full_credit_once_correct() {
+        let qs = [first_try(), third_try(), skipped(), skipped()];
+        assert_eq!(score_percent(&qs, ScoringPolicy::Lenient), Some(50));
+    }
+
+    #[test]
+    fn first_try_counts_only_the_first_attempt() {
+        let qs = [first_try(), third_try(), skipped(), skipped()];
+        assert_eq!(score_percent(&qs, ScoringPolicy::FirstTry), Some(25));
+    }
+
+    #[test]
+    fn decaying_takes_a_quarter_per_extra_try_down_to_a_floor() {
+        let p = ScoringPolicy::Decaying;
+        assert_eq!(p.question_credit(&first_try()), 1.0);
+        assert_eq!(p.question_credit(&third_try()), 0.5);
+        assert_eq!(p.question_credit(&progress(9, true, false, 1.0, 0.0)), 0.25);
+        assert_eq!(p.question_credit(&skipped()), 0.0);
+    }
+
+    #[test]
+    fn partial_credit_is_kept_when_moving_on() {
+        let partial = progress(2, false, true, 0.5, 0.25);
+        assert_eq!(ScoringPolicy::Lenient.question_credit(&partial), 0.5);
+        assert_eq!(ScoringPolicy::FirstTry.question_credit(&partial), 0.25);
+        assert_eq!(ScoringPolicy::Decaying.question_credit(&partial), 0.0);
+    }
+
+    #[test]
+    fn empty_quiz_has_no_score() {
+        assert_eq!(score_percent(&[], ScoringPolicy::Lenient), None);
+    }
+
+    #[test]
+    fn accuracy_counts_correct_and_first_try() {
+        let acc = Accuracy::from_progress(&[first_try(), third_try(), skipped()]);
+        assert_eq!((acc.correct, acc.first_try_correct, acc.total), (2, 1, 3));
+        assert_eq!(acc.percent(), 67);
+        assert_eq!(acc.first_try_percent(), 33);
+        assert_eq!(Accuracy::from_progress(&[]).percent(), 0);
+    }
+}
@@ -0,0 +1,126 @@
+// E:\rust_dev\kids_story_trainer\src\speech.rs
+//
+// Read-aloud through the browser's SpeechSynthesis API. Nothing is sent
+// anywhere: the browser's own voices do the talking. Whether narration is
+// allowed is a per-session choice (see `Narration`), never saved, so a
+// reading assessment isn't quietly turned into a listening one.
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{SpeechSynthesis, SpeechSynthesisUtterance, SpeechSynthesisVoice};
+use yew::Callback;
+
+// Speaking speeds offered to grown-ups, slowest first.
+pub const RATES: &[(f32, &str)] = &[
+    (0.6, "Slow"),
+    (0.8, "A little slow"),
+    (1.0, "Normal"),
+    (1.2, "A little fast"),
+];
+
+// How to read aloud this session. `voice` is a voice URI from `voices()`;
+// None uses the browser's default voice.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Narration {
+    pub voice: Option<String>,
+    pub rate: f32,
+}
+
+impl Default for Narration {
+    fn default() -> Self {
+        Narration {
+            voice: None,
+            rate: 0.8,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Voice {
+    pub uri: String,
+    pub label: String,
+}
+
+fn synth() -> Option<SpeechSynthesis> {
+    web_sys::window()?.speech_synthesis().ok()
+}
+
+pub fn is_supported() -> bool {
+    synth().is_some()
+}
+
+fn browser_voices() -> Vec<SpeechSynthesisVoice> {
+    let Some(synth) = synth() else {
+        return Vec::new();
+    };
+    synth
+        .get_voices()
+        .iter()
+        .filter_map(|v| v.dyn_into::<SpeechSynthesisVoice>().ok())
+        .collect()
+}
+
+// Installed voices, English ones first. Browsers load voices lazily, so
+// this can be empty until `watch_voices` reports them.
+pub fn voices() -> Vec<Voice> {
+    let mut voices: Vec<Voice> = browser_voices()
+        .iter()
+        .map(|v| Voice {
+            uri: v.voice_uri(),
+            label: format!("{} ({})", v.name(), v.lang()),
+        })
+        .collect();
+    voices.sort_by_key(|v| !v.label.contains("(en"));
+    voices
+}
+
+// Calls `on_change` whenever the browser's voice list changes. Returns the
+// cleanup that stops listening.
+pub fn watch_voices(on_change: Callback<Vec<Voice>>) -> impl FnOnce() {
+    let synth = synth();
+    let listener = Closure::<dyn Fn()>::new(move || on_change.emit(voices()));
+    if let Some(synth) = &synth {
+        synth.set_onvoiceschanged(Some(listener.as_ref().unchecked_ref()));
+    }
+    move || {
+        if let Some(synth) = &synth {
+            synth.set_onvoiceschanged(None);
+        }
+        drop(listener);
+    }
+}
+
+// Reads `text` aloud, cutting off anything already being read.
+pub fn speak(text: &str, narration: &Narration) {
+    let Some(synth) = synth() else {
+        return;
+    };
+    synth.cancel();
+    let Ok(utterance) = SpeechSynthesisUtterance::new_with_text(text) else {
+        return;
+    };
+    utterance.set_rate(narration.rate);
+    if let Some(uri) = &narration.voice {
+        if let Some(voice) = browser_voices().iter().find(|v| v.voice_uri() == *uri) {
+            utterance.set_voice(Some(voice));
+        }
+    }
+    synth.speak(&utterance);
+}
+
+pub fn pause() {
+    if let Some(synth) = synth() {
+        synth.pause();
+    }
+}
+
+pub fn resume() {
+    if let Some(synth) = synth() {
+        synth.resume();
+    }
+}
+
+pub fn stop() {
+    if let Some(synth) = synth() {
+        synth.cancel();
+    }
+}
@@ -0,0 +1,275 @@
+// E:\rust_dev\kids_story_trainer\src\storage.rs
+//
+// Opt-in, local-only persistence. Nothing here ever leaves the browser: the
+// whole save file lives under one localStorage key and is only written after
+// a grown-up turns on "Save reading history on this device".
+use serde::{Deserialize, Serialize};
+
+use crate::adaptive::DifficultyLevel;
+use crate::moderation::BlockedTopic;
+use crate::pack::StoryPack;
+use crate::{AppSettings, QuestionProgress, Story};
+
+const STORAGE_KEY: &str = "kids_story_trainer";
+
+// Imported story packs. Teacher content rather than anything about the
+// child, so it is kept whether or not history saving is turned on.
+const LIBRARY_KEY: &str = "kids_story_trainer_library";
+
+// A story a grown-up edited and locked: students replay it instead of
+// generating new ones until it is unlocked.
+const LOCKED_STORY_KEY: &str = "kids_story_trainer_locked_story";
+
+// Bump when the saved shape changes, and teach `load` how to upgrade the
+// older files.
+pub const SCHEMA_VERSION: u32 = 1;
+
+// Same for the story-pack library (`migrate_library`).
+pub const LIBRARY_SCHEMA_VERSION: u32 = 1;
+
+// Oldest sessions are dropped past this so we stay well inside the
+// browser's localStorage quota.
+const MAX_SESSIONS: usize = 200;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct SessionRecord {
+    pub story: Story,
+    pub progress: Vec<QuestionProgress>,
+    pub score_percent: Option<i32>,
+    pub used_ai: bool,
+    // Level the story was requested at; missing in older saves.
+    #[serde(default)]
+    pub level: Option<DifficultyLevel>,
+    // Milliseconds since the Unix epoch (JS `Date.now()`).
+    pub completed_at: f64,
+}
+
+// The library save file. Version 0 was a bare list of packs.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+struct LibraryData {
+    schema_version: u32,
+    packs: Vec<StoryPack>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct SavedData {
+    pub schema_version: u32,
+    pub settings: AppSettings,
+    pub sessions: Vec<SessionRecord>,
+    // Topics the safety filter refused; missing in older saves.
+    #[serde(default)]
+    pub blocked_topics: Vec<BlockedTopic>,
+}
+
+// Why a save file could not be used. Loading never panics; the app just
+// starts fresh and shows the message.
+#[derive(Clone, PartialEq, Debug)]
+pub enum LoadError {
+    Unavailable,
+    Corrupt(String),
+    FromNewerVersion(u32),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Unavailable => write!(f, "This browser does not allow saving data."),
+            LoadError::Corrupt(e) => write!(f, "Saved history could not be read ({}).", e),
+            LoadError::FromNewerVersion(v) => write!(
+                f,
+                "Saved history is from a newer version of the app (v{}); it was left untouched.",
+                v
+            ),
+        }
+    }
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+// `Ok(None)` means nothing has been saved (the grown-up has not opted in).
+pub fn load() -> Result<Option<SavedData>, LoadError> {
+    let storage = local_storage().ok_or(LoadError::Unavailable)?;
+    let Some(raw) = storage.get_item(STORAGE_KEY).ok().flatten() else {
+        return Ok(None);
+    };
+    parse_saved(&raw).map(Some)
+}
+
+// Every shipped save file has carried `schema_version`, so a missing one
+// reads as corrupt rather than as an older version.
+fn parse_saved(raw: &str) -> Result<SavedData, LoadError> {
+    let value: serde_json::Value =
+        serde_json::from_str(raw).map_err(|e| LoadError::Corrupt(e.to_string()))?;
+    if let Some(version) = value.get("schema_version").and_then(|v| v.as_u64()) {
+        if version > SCHEMA_VERSION as u64 {
+            return Err(LoadError::FromNewerVersion(version as u32));
+        }
+    }
+    serde_json::from_value(value).map_err(|e| LoadError::Corrupt(e.to_string()))
+}
+
+// Like `save_library`, refuses to replace a history that is there but
+// can't be read, so the "left untouched" message stays true.
+pub fn save(data: &SavedData) -> Result<(), String> {
+    let storage = local_storage().ok_or_else(|| "localStorage is not available".to_string())?;
+    if let Some(raw) = storage.get_item(STORAGE_KEY).ok().flatten() {
+        if parse_saved(&raw).is_err() {
+            return Err(
+                "the reading history already saved here could not be read, so it was not replaced"
+                    .to_string(),
+            );
+        }
+    }
+    let mut data = data.clone();
+    data.schema_version = SCHEMA_VERSION;
+    if data.sessions.len() > MAX_SESSIONS {
+        let excess = data.sessions.len() - MAX_SESSIONS;
+        data.sessions.drain(..excess);
+    }
+    let raw = serde_json::to_string(&data).map_err(|e| e.to_string())?;
+    storage
+        .set_item(STORAGE_KEY, &raw)
+        .map_err(|_| "the browser refused to save (storage may be full)".to_string())
+}
+
+// "Clear reading history and settings": removes the save file entirely.
+// Story packs and the locked story are teacher content under their own
+// keys and are left alone; they are removed from their own Settings rows.
+pub fn clear() {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(STORAGE_KEY);
+    }
+}
+
+pub fn load_library() -> Result<Vec<StoryPack>, LoadError> {
+    let storage = local_storage().ok_or(LoadError::Unavailable)?;
+    let Some(raw) = storage.get_item(LIBRARY_KEY).ok().flatten() else {
+        return Ok(Vec::new());
+    };
+    parse_library(&raw)
+}
+
+fn parse_library(raw: &str) -> Result<Vec<StoryPack>, LoadError> {
+    let value: serde_json::Value =
+        serde_json::from_str(raw).map_err(|e| LoadError::Corrupt(e.to_string()))?;
+    let version = value
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32;
+    if version > LIBRARY_SCHEMA_VERSION {
+        return Err(LoadError::FromNewerVersion(version));
+    }
+
+    let value = migrate_library(value, version);
+    serde_json::from_value::<LibraryData>(value)
+        .map(|data| data.packs)
+        .map_err(|e| LoadError::Corrupt(e.to_string()))
+}
+
+// Upgrades a version 0 library (a bare list of packs).
+fn migrate_library(value: serde_json::Value, from_version: u32) -> serde_json::Value {
+    if from_version < 1 {
+        return serde_json::json!({ "schema_version": 1, "packs": value });
+    }
+    value
+}
+
+// Refuses to replace a library that is there but can't be read, so a bad
+// or newer save file is never wiped by the next import.
+pub fn save_library(packs: &[StoryPack]) -> Result<(), String> {
+    let storage = local_storage().ok_or_else(|| "localStorage is not available".to_string())?;
+    if let Some(raw) = storage.get_item(LIBRARY_KEY).ok().flatten() {
+        if parse_library(&raw).is_err() {
+            return Err(
+                "the story packs already saved here could not be read, so they were not replaced"
+                    .to_string(),
+            );
+        }
+    }
+    if packs.is_empty() {
+        let _ = storage.remove_item(LIBRARY_KEY);
+        return Ok(());
+    }
+    let data = LibraryData {
+        schema_version: LIBRARY_SCHEMA_VERSION,
+        packs: packs.to_vec(),
+    };
+    let raw = serde_json::to_string(&data).map_err(|e| e.to_string())?;
+    storage
+        .set_item(LIBRARY_KEY, &raw)
+        .map_err(|_| "the browser refused to save (storage may be full)".to_string())
+}
+
+// A locked story that can't be read is treated as no lock at all.
+pub fn load_locked_story() -> Option<Story> {
+    let raw = local_storage()?.get_item(LOCKED_STORY_KEY).ok().flatten()?;
+    serde_json::from_str(&raw).ok()
+}
+
+// `None` unlocks.
+pub fn save_locked_story(story: Option<&Story>) -> Result<(), String> {
+    let storage = local_storage().ok_or_else(|| "localStorage is not available".to_string())?;
+    let Some(story) = story else {
+        let _ = storage.remove_item(LOCKED_STORY_KEY);
+        return Ok(());
+    };
+    let raw = serde_json::to_string(story).map_err(|e| e.to_string())?;
+    storage
+        .set_item(LOCKED_STORY_KEY, &raw)
+        .map_err(|_| "the browser refused to save (storage may be full)".to_string())
+}
+
+pub fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn library_round_trips_with_a_version() {
+        let packs = vec![crate::pack::new_pack("Ocean")];
+        let raw = serde_json::to_string(&LibraryData {
+            schema_version: LIBRARY_SCHEMA_VERSION,
+            packs: packs.clone(),
+        })
+        .unwrap();
+        assert_eq!(parse_library(&raw), Ok(packs));
+    }
+
+    #[test]
+    fn bare_list_library_is_migrated() {
+        let packs = vec![crate::pack::new_pack("Ocean")];
+        let raw = serde_json::to_string(&packs).unwrap();
+        assert_eq!(parse_library(&raw), Ok(packs));
+    }
+
+    #[test]
+    fn unreadable_or_newer_library_is_an_error() {
+        assert!(matches!(parse_library("{oops"), Err(LoadError::Corrupt(_))));
+        assert!(matches!(
+            parse_library(r#"{"schema_version": 1, "packs": [{"name": 3}]}"#),
+            Err(LoadError::Corrupt(_))
+        ));
+        assert_eq!(
+            parse_library(r#"{"schema_version": 99, "packs": []}"#),
+            Err(LoadError::FromNewerVersion(99))
+        );
+    }
+
+    #[test]
+    fn unreadable_or_newer_history_is_an_error() {
+        assert!(matches!(parse_saved("{oops"), Err(LoadError::Corrupt(_))));
+        assert!(matches!(
+            parse_saved(r#"{"sessions": []}"#),
+            Err(LoadError::Corrupt(_))
+        ));
+        assert_eq!(
+            parse_saved(r#"{"schema_version": 99}"#),
+            Err(LoadError::FromNewerVersion(99))
+        );
+    }
+}
@@ -0,0 +1,250 @@
+// E:\rust_dev\kids_story_trainer\src\stream.rs
+//
+// Streaming story replies. A Worker asked for `"stream": true` answers with
+// one JSON event per line, either as NDJSON or as server-sent events
+// (`data: {...}`); both are read the same way. The title and paragraphs
+// arrive first so the child can start reading, the questions last:
+//
+//   {"type":"title","title":"The Lost Kite"}
+//   {"type":"paragraph","text":"Maya ran to the hill..."}
+//   {"type":"question","text":"...","paragraph_index":0,"kind":"true_false","answer":true}
+//   {"type":"done"}
+use serde::Deserialize;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::provider::{CancelToken, ProviderError, Reply};
+use crate::{Question, Story};
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    Title {
+        title: String,
+    },
+    GradeLevel {
+        grade_level: u8,
+    },
+    Paragraph {
+        text: String,
+    },
+    Question(Question),
+    Questions {
+        questions: Vec<Question>,
+    },
+    Done,
+    Error {
+        message: String,
+    },
+}
+
+// Builds up the story as events arrive.
+#[derive(Default)]
+struct StoryAssembler {
+    story: Story,
+    done: bool,
+}
+
+impl StoryAssembler {
+    // Returns true when the visible part of the story changed.
+    fn apply(&mut self, event: StreamEvent) -> Result<bool, ProviderError> {
+        match event {
+            StreamEvent::Title { title } => self.story.title = title,
+            StreamEvent::GradeLevel { grade_level } => {
+                self.story.grade_level = Some(grade_level);
+                return Ok(false);
+            }
+            StreamEvent::Paragraph { text } => self.story.paragraphs.push(text),
+            StreamEvent::Question(q) => {
+                self.story.questions.push(q);
+                return Ok(false);
+            }
+            StreamEvent::Questions { questions } => {
+                self.story.questions.extend(questions);
+                return Ok(false);
+            }
+            StreamEvent::Done => self.done = true,
+            StreamEvent::Error { message } => return Err(ProviderError::Parse(message)),
+        }
+        Ok(true)
+    }
+
+    // Applies every complete line in `buffer`, leaving a partial last line
+    // for the next chunk. Returns true when the visible story changed.
+    fn feed(&mut self, buffer: &mut Vec<u8>) -> Result<bool, ProviderError> {
+        let mut changed = false;
+        while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            if let Some(event) = parse_line(&String::from_utf8_lossy(&line))? {
+                changed |= self.apply(event)?;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+// Parses one NDJSON or SSE line. SSE field lines other than `data:` and
+// comments are skipped, as is the `[DONE]` marker some servers send.
+fn parse_line(line: &str) -> Result<Option<StreamEvent>, ProviderError> {
+    let line = line.trim();
+    let json = match line.strip_prefix("data:") {
+        Some(data) => data.trim(),
+        None if line.starts_with('{') => line,
+        None => return Ok(None),
+    };
+    if json.is_empty() || json == "[DONE]" {
+        return Ok(None);
+    }
+    serde_json::from_str(json)
+        .map(Some)
+        .map_err(|e| ProviderError::Parse(format!("bad stream event: {}", e)))
+}
+
+// Reads the streamed story, calling `on_progress` with the story so far
+// each time the title or a paragraph arrives. The story is only returned
+// once the server says it is done. Every chunk restarts the request's
+// timeout, so only a stalled stream times out.
+pub async fn read_story(
+    reply: Reply,
+    cancel: &CancelToken,
+    on_progress: &dyn Fn(&Story),
+) -> Result<Story, ProviderError> {
+    let body = reply
+        .resp
+        .body()
+        .ok_or_else(|| ProviderError::Parse("empty stream".into()))?;
+    let reader: web_sys::ReadableStreamDefaultReader = body.get_reader().unchecked_into();
+
+    let mut assembler = StoryAssembler::default();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut finished = false;
+
+    while !finished {
+        let chunk = JsFuture::from(reader.read()).await.map_err(|e| {
+            reply.error(
+                cancel,
+                ProviderError::Network(format!("stream interrupted ({:?})", e)),
+            )
+        })?;
+        reply.keep_alive();
+        finished = js_sys::Reflect::get(&chunk, &"done".into())
+            .map(|d| d.is_truthy())
+            .unwrap_or(true);
+        if let Ok(value) = js_sys::Reflect::get(&chunk, &"value".into()) {
+            if !value.is_undefined() {
+                buffer.extend(js_sys::Uint8Array::new(&value).to_vec());
+            }
+        }
+        if finished {
+            // Last line may have no trailing newline.
+            buffer.push(b'\n');
+        }
+
+        let changed = assembler.feed(&mut buffer)?;
+        if changed && !assembler.done {
+            on_progress(&assembler.story);
+        }
+    }
+
+    if !assembler.done {
+        return Err(ProviderError::Network("story stream ended early".into()));
+    }
+    Ok(assembler.story)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Feeds `text` in chunks of `size` bytes, like a network stream.
+    fn assemble(text: &str, size: usize) -> Result<StoryAssembler, ProviderError> {
+        let mut assembler = StoryAssembler::default();
+        let mut buffer = Vec::new();
+        for chunk in text.as_bytes().chunks(size) {
+            buffer.extend_from_slice(chunk);
+            assembler.feed(&mut buffer)?;
+        }
+        buffer.push(b'\n');
+        assembler.feed(&mut buffer)?;
+        Ok(assembler)
+    }
+
+    const NDJSON: &str = r#"{"type":"title","title":"The Lost Kite"}
+{"type":"grade_level","grade_level":2}
+{"type":"paragraph","text":"Maya ran to the hill."}
+{"type":"question","text":"Did Maya run?","paragraph_index":0,"kind":"true_false","answer":true}
+{"type":"questions","questions":[{"text":"Where?","paragraph_index":0,"kind":"short_answer","accepted_answers":["the hill"]}]}
+{"type":"done"}"#;
+
+    #[test]
+    fn ndjson_builds_the_story() {
+        for size in [1, 7, NDJSON.len()] {
+            let assembler = assemble(NDJSON, size).unwrap();
+            assert!(assembler.done);
+            let story = assembler.story;
+            assert_eq!(story.title, "The Lost Kite");
+            assert_eq!(story.grade_level, Some(2));
+            assert_eq!(story.paragraphs, vec!["Maya ran to the hill."]);
+            assert_eq!(story.questions.len(), 2);
+            assert_eq!(story.questions[1].text, "Where?");
+        }
+    }
+
+    #[test]
+    fn sse_data_lines_are_read_and_other_lines_skipped() {
+        let sse = "event: story\r\n\
+            : keep-alive comment\r\n\
+            data: {\"type\":\"title\",\"title\":\"Kites\"}\r\n\
+            \r\n\
+            data:{\"type\":\"paragraph\",\"text\":\"Up it went.\"}\r\n\
+            \r\n\
+            data: {\"type\":\"done\"}\r\n\
+            data: [DONE]\r\n";
+        let assembler = assemble(sse, 5).unwrap();
+        assert!(assembler.done);
+        assert_eq!(assembler.story.title, "Kites");
+        assert_eq!(assembler.story.paragraphs, vec!["Up it went."]);
+    }
+
+    #[test]
+    fn only_title_and_paragraphs_count_as_visible_changes() {
+        let mut assembler = StoryAssembler::default();
+        let mut feed = |line: &str| {
+            let mut buffer = format!("{}\n", line).into_bytes();
+            assembler.feed(&mut buffer).unwrap()
+        };
+        assert!(feed(r#"{"type":"title","title":"Kites"}"#));
+        assert!(!feed(r#"{"type":"grade_level","grade_level":1}"#));
+        assert!(!feed(
+            r#"{"type":"question","text":"Q?","paragraph_index":0,"kind":"true_false","answer":false}"#
+        ));
+        assert!(!feed(""));
+        assert!(feed(r#"{"type":"done"}"#));
+    }
+
+    #[test]
+    fn a_stream_without_done_is_not_finished() {
+        let text = NDJSON.trim_end_matches(r#"{"type":"done"}"#);
+        assert!(!assemble(text, 16).unwrap().done);
+    }
+
+    #[test]
+    fn malformed_or_error_events_fail_the_stream() {
+        let bad = NDJSON.replace(
+            r#"{"type":"paragraph","text":"Maya ran to the hill."}"#,
+            r#"{"type":"paragraph","text":"Maya ran"#,
+        );
+        assert!(matches!(assemble(&bad, 9), Err(ProviderError::Parse(_))));
+        let unknown = NDJSON.replace(r#""type":"grade_level""#, r#""type":"mystery""#);
+        assert!(matches!(
+            assemble(&unknown, 9),
+            Err(ProviderError::Parse(_))
+        ));
+        let error = r#"{"type":"title","title":"Kites"}
+{"type":"error","message":"model overloaded"}"#;
+        assert_eq!(
+            assemble(error, 4).err(),
+            Some(ProviderError::Parse("model overloaded".into()))
+        );
+    }
+}
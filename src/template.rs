@@ -0,0 +1,602 @@
+// E:\rust_dev\kids_story_trainer\src\template.rs
+//
+// Offline story templates. When no built-in or imported story fits the
+// kid's topic, a story is put together on the device: the topic picks a
+// word bank (dinosaurs, ocean, space, ...) from word_banks.json, and its
+// places, helpers, objects and discoveries fill one story arc plus a pool
+// of questions. Every choice list is built from the same slots, so the
+// correct answers are always right for the story that was written.
+use std::sync::OnceLock;
+
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng};
+use serde::Deserialize;
+
+use crate::fallback::word_key;
+use crate::{AnswerMatching, Question, QuestionKind, Story};
+
+const WORD_BANKS: &str = include_str!("word_banks.json");
+
+// Longer topics are cut to this many words before they go into sentences.
+const MAX_TOPIC_WORDS: usize = 6;
+
+#[derive(Deserialize)]
+struct WordBanks {
+    names: Vec<String>,
+    days: Vec<String>,
+    hiding_spots: Vec<String>,
+    // Used when no category's keywords match the topic.
+    general: Category,
+    categories: Vec<Category>,
+}
+
+// Keywords written with a capital ("Mars") are proper nouns and keep their
+// capital when a topic starts with them.
+#[derive(Deserialize)]
+struct Category {
+    keywords: Vec<String>,
+    places: Vec<String>,
+    helpers: Vec<String>,
+    objects: Vec<String>,
+    discoveries: Vec<String>,
+}
+
+fn banks() -> &'static WordBanks {
+    static BANKS: OnceLock<WordBanks> = OnceLock::new();
+    BANKS.get_or_init(|| serde_json::from_str(WORD_BANKS).expect("word_banks.json is valid"))
+}
+
+// Paragraph templates, in story order. Each line is (lowest grade, text);
+// harder sentences are left out for younger readers. The first line of
+// MEET must stay first: the evidence question points at it.
+const INTRO: &[(u8, &str)] = &[
+    (0, "{name} loved {topic}."),
+    (0, "{name} read every book about {topic} at school."),
+    (3, "At night, {name} drew pictures and wrote questions in a notebook, hoping to find the answers one day."),
+];
+const TRIP: &[(u8, &str)] = &[
+    (0, "One {day}, {name} and {friend} went to {place}."),
+    (0, "They wanted to learn more about {topic}."),
+    (0, "They packed {an_object} and a snack."),
+    (4, "{friend} made a list of questions so they would not forget anything important."),
+];
+const MEET: &[(u8, &str)] = &[
+    (0, "At {the_place}, they met {helper}."),
+    (0, "{The_helper} showed them {discovery}."),
+    (2, "{name} and {friend} were amazed and asked question after question."),
+];
+const PROBLEM: &[(u8, &str)] = &[
+    (0, "Later, {friend} could not find the {object}."),
+    (0, "The two friends looked everywhere."),
+    (3, "They stayed calm, thought about every place they had been, and checked each one again."),
+    (0, "At last, {name} found the {object} {hiding_spot}."),
+];
+const LESSON: &[(u8, &str)] = &[
+    (0, "Before going home, they wrote down three new facts about {topic}."),
+    (2, "{name} learned that asking questions is a great way to discover new things."),
+    (5, "{friend} realized that a careful plan had made the whole day easier."),
+];
+const ENDING: &[(u8, &str)] = &[
+    (0, "Back at school, {name} and {friend} shared their notes with the class."),
+    (0, "Everyone wanted to visit {the_place} next."),
+];
+const ARC: [&[(u8, &str)]; 6] = [INTRO, TRIP, MEET, PROBLEM, LESSON, ENDING];
+// Positions in ARC.
+const INTRO_P: usize = 0;
+const TRIP_P: usize = 1;
+const MEET_P: usize = 2;
+const PROBLEM_P: usize = 3;
+const LESSON_P: usize = 4;
+const ENDING_P: usize = 5;
+
+// Which parts of the arc to tell for each story length (1–6 paragraphs).
+fn arc_for_length(paragraphs: usize) -> &'static [usize] {
+    match paragraphs {
+        0 | 1 => &[TRIP_P],
+        2 => &[TRIP_P, MEET_P],
+        3 => &[INTRO_P, TRIP_P, MEET_P],
+        4 => &[INTRO_P, TRIP_P, MEET_P, LESSON_P],
+        5 => &[INTRO_P, TRIP_P, MEET_P, PROBLEM_P, LESSON_P],
+        _ => &[INTRO_P, TRIP_P, MEET_P, PROBLEM_P, LESSON_P, ENDING_P],
+    }
+}
+
+// Everything the templates can mention, chosen once per story.
+struct Slots {
+    topic: String,
+    name: String,
+    friend: String,
+    day: String,
+    place: String,
+    helper: String,
+    object: String,
+    discovery: String,
+    hiding_spot: String,
+}
+
+impl Slots {
+    fn fill(&self, template: &str) -> String {
+        template
+            .replace("{topic}", &self.topic)
+            .replace("{name}", &self.name)
+            .replace("{friend}", &self.friend)
+            .replace("{day}", &self.day)
+            .replace("{place}", &self.place)
+            .replace("{the_place}", &definite(&self.place))
+            .replace("{helper}", &self.helper)
+            .replace("{The_helper}", &capitalize(&definite(&self.helper)))
+            .replace("{the_helper}", &definite(&self.helper))
+            .replace("{an_object}", &indefinite(&self.object))
+            .replace("{object}", &self.object)
+            .replace("{discovery}", &self.discovery)
+            .replace("{hiding_spot}", &self.hiding_spot)
+    }
+}
+
+// A question candidate, tied to one part of the arc.
+struct Candidate {
+    part: usize,
+    text: String,
+    kind: QuestionKind,
+}
+
+// Writes a story about `topic` for the given grade and length. Never fails
+// and needs no network.
+pub fn generate_story(topic: &str, grade_level: u8, num_paragraphs: u8, num_questions: u8) -> Story {
+    let mut rng = thread_rng();
+    let banks = banks();
+    let category = match_category(banks, topic);
+
+    let mut names: Vec<&String> = banks.names.iter().collect();
+    names.shuffle(&mut rng);
+    let pick = |list: &[String], rng: &mut rand::rngs::ThreadRng| {
+        list.choose(rng).cloned().unwrap_or_default()
+    };
+    let slots = Slots {
+        topic: display_topic(banks, topic),
+        name: names.first().map(|n| n.to_string()).unwrap_or_default(),
+        friend: names.get(1).map(|n| n.to_string()).unwrap_or_default(),
+        day: pick(&banks.days, &mut rng),
+        place: pick(&category.places, &mut rng),
+        helper: pick(&category.helpers, &mut rng),
+        object: pick(&category.objects, &mut rng),
+        discovery: pick(&category.discoveries, &mut rng),
+        hiding_spot: pick(&banks.hiding_spots, &mut rng),
+    };
+
+    let parts = arc_for_length(num_paragraphs.clamp(1, 6) as usize);
+    let paragraphs: Vec<String> = parts
+        .iter()
+        .map(|&part| {
+            ARC[part]
+                .iter()
+                .filter(|(min_grade, _)| grade_level >= *min_grade)
+                .map(|(_, line)| slots.fill(line))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+
+    let title = if rng.gen_bool(0.5) {
+        format!("{} and {} Explore {}", slots.name, slots.friend, title_case(&slots.topic))
+    } else {
+        let place = definite(&slots.place);
+        format!("A Day at the {}", title_case(place.trim_start_matches("the ")))
+    };
+
+    let candidates = question_pool(banks, &slots, grade_level, parts, &mut rng);
+    let questions = choose_questions(candidates, parts, num_questions.clamp(1, 10) as usize, &mut rng);
+
+    Story {
+        title,
+        paragraphs,
+        questions,
+        grade_level: Some(grade_level),
+    }
+}
+
+// Every question the story could ask, given which parts are told.
+fn question_pool(
+    banks: &WordBanks,
+    slots: &Slots,
+    grade_level: u8,
+    parts: &[usize],
+    rng: &mut impl Rng,
+) -> Vec<Candidate> {
+    let every = |field: fn(&Category) -> &Vec<String>| -> Vec<String> {
+        let mut all: Vec<String> = banks
+            .categories
+            .iter()
+            .chain(std::iter::once(&banks.general))
+            .flat_map(|c| field(c).iter().cloned())
+            .collect();
+        all.sort();
+        all.dedup();
+        all
+    };
+    let mut pool = vec![
+        Candidate {
+            part: INTRO_P,
+            text: slots.fill("{name} loved {topic}."),
+            kind: QuestionKind::TrueFalse { answer: true },
+        },
+        Candidate {
+            part: TRIP_P,
+            text: slots.fill("Where did {name} and {friend} go?"),
+            kind: multiple_choice(&slots.place, &every(|c| &c.places), rng),
+        },
+        Candidate {
+            part: TRIP_P,
+            text: slots.fill("Who went to {the_place} with {name}?"),
+            kind: QuestionKind::ShortAnswer {
+                accepted_answers: vec![slots.friend.clone()],
+                matching: AnswerMatching::default(),
+            },
+        },
+        Candidate {
+            part: MEET_P,
+            text: slots.fill("What did {the_helper} show them?"),
+            kind: multiple_choice(&slots.discovery, &every(|c| &c.discoveries), rng),
+        },
+        Candidate {
+            part: MEET_P,
+            text: slots.fill("Which sentence tells who {name} and {friend} met?"),
+            kind: QuestionKind::EvidenceSelect { correct_sentence: 0 },
+        },
+        Candidate {
+            part: PROBLEM_P,
+            text: slots.fill("What could {friend} not find?"),
+            kind: multiple_choice(
+                &indefinite(&slots.object),
+                &every(|c| &c.objects)
+                    .iter()
+                    .map(|o| indefinite(o))
+                    .collect::<Vec<_>>(),
+                rng,
+            ),
+        },
+        Candidate {
+            part: PROBLEM_P,
+            text: slots.fill("Who found the {object}?"),
+            kind: QuestionKind::ShortAnswer {
+                accepted_answers: vec![slots.name.clone()],
+                matching: AnswerMatching::default(),
+            },
+        },
+        Candidate {
+            part: LESSON_P,
+            text: "What did they write down before going home?".to_string(),
+            kind: multiple_choice(
+                &slots.fill("Three new facts about {topic}"),
+                &[
+                    "A list of groceries".to_string(),
+                    "A letter to a friend".to_string(),
+                    "The rules of a game".to_string(),
+                ],
+                rng,
+            ),
+        },
+    ];
+
+    // A packed object from another word bank makes a fair "false".
+    let other_objects: Vec<String> = every(|c| &c.objects)
+        .into_iter()
+        .filter(|o| *o != slots.object)
+        .collect();
+    if let Some(other) = other_objects.choose(rng) {
+        pool.push(Candidate {
+            part: TRIP_P,
+            text: format!("{} and {} packed {}.", slots.name, slots.friend, indefinite(other)),
+            kind: QuestionKind::TrueFalse { answer: false },
+        });
+    }
+
+    // "amazed" is only in the story from grade 2 up.
+    if grade_level >= 2 {
+        let (choices, correct_index) = shuffled_choices(
+            "Very surprised",
+            &[
+                "Very sleepy".to_string(),
+                "A little bored".to_string(),
+                "Very angry".to_string(),
+            ],
+            rng,
+        );
+        pool.push(Candidate {
+            part: MEET_P,
+            text: "What does \"amazed\" mean in this story?".to_string(),
+            kind: QuestionKind::Vocabulary {
+                word: "amazed".to_string(),
+                choices,
+                correct_index,
+            },
+        });
+    }
+
+    // Put-in-order needs at least three of the story's events.
+    let events: Vec<(usize, String)> = [
+        (TRIP_P, "{name} and {friend} went to {place}."),
+        (MEET_P, "They met {helper}."),
+        (PROBLEM_P, "{friend} lost the {object}."),
+        (LESSON_P, "They wrote down facts about {topic}."),
+        (ENDING_P, "They shared their notes with the class."),
+    ]
+    .iter()
+    .filter(|(part, _)| parts.contains(part))
+    .map(|(part, line)| (*part, capitalize(&slots.fill(line))))
+    .collect();
+    if events.len() >= 3 {
+        let last_part = events.last().map(|(part, _)| *part).unwrap_or(TRIP_P);
+        let mut shown: Vec<usize> = (0..events.len()).collect();
+        shown.shuffle(rng);
+        if shown.windows(2).all(|w| w[0] < w[1]) {
+            shown.rotate_left(1);
+        }
+        // correct_order lists positions in `shown`, first event to last
+        let correct_order = (0..events.len())
+            .map(|e| shown.iter().position(|&s| s == e).unwrap_or(0))
+            .collect();
+        pool.push(Candidate {
+            part: last_part,
+            text: "Put these events in the order they happened.".to_string(),
+            kind: QuestionKind::Sequence {
+                events: shown.iter().map(|&i| events[i].1.clone()).collect(),
+                correct_order,
+            },
+        });
+    }
+
+    pool.retain(|c| parts.contains(&c.part));
+    pool
+}
+
+// Up to `count` questions, a different kind each where possible, in story
+// order; `paragraph_index` is mapped from arc part to actual paragraph.
+fn choose_questions(
+    mut pool: Vec<Candidate>,
+    parts: &[usize],
+    count: usize,
+    rng: &mut impl Rng,
+) -> Vec<Question> {
+    pool.shuffle(rng);
+    let mut chosen: Vec<Candidate> = Vec::new();
+    let mut rest: Vec<Candidate> = Vec::new();
+    for candidate in pool {
+        let new_kind = !chosen
+            .iter()
+            .any(|c| c.kind.label() == candidate.kind.label());
+        if new_kind && chosen.len() < count {
+            chosen.push(candidate);
+        } else {
+            rest.push(candidate);
+        }
+    }
+    chosen.extend(rest.into_iter().take(count.saturating_sub(chosen.len())));
+    chosen.sort_by_key(|c| c.part);
+
+    chosen
+        .into_iter()
+        .map(|c| Question {
+            text: c.text,
+            paragraph_index: parts.iter().position(|&p| p == c.part).unwrap_or(0),
+            kind: c.kind,
+        })
+        .collect()
+}
+
+fn multiple_choice(correct: &str, others: &[String], rng: &mut impl Rng) -> QuestionKind {
+    let (choices, correct_index) = shuffled_choices(correct, others, rng);
+    QuestionKind::MultipleChoice {
+        choices,
+        correct_index,
+    }
+}
+
+// The correct answer plus up to three different distractors, shuffled, and
+// where the correct one ended up.
+fn shuffled_choices(correct: &str, others: &[String], rng: &mut impl Rng) -> (Vec<String>, usize) {
+    let mut choices: Vec<String> = others
+        .choose_multiple(rng, others.len())
+        .filter(|o| !o.eq_ignore_ascii_case(correct))
+        .take(3)
+        .map(|o| capitalize(o))
+        .collect();
+    choices.push(capitalize(correct));
+    choices.shuffle(rng);
+    let correct_index = choices
+        .iter()
+        .position(|c| c.eq_ignore_ascii_case(correct))
+        .unwrap_or(0);
+    (choices, correct_index)
+}
+
+// The category with the most keyword hits in the topic, else "general".
+fn match_category<'a>(banks: &'a WordBanks, topic: &str) -> &'a Category {
+    let topic_keys: Vec<String> = topic_words(topic).map(|w| word_key(&w)).collect();
+    banks
+        .categories
+        .iter()
+        .map(|c| {
+            let hits = c
+                .keywords
+                .iter()
+                .filter(|k| topic_keys.contains(&word_key(&k.to_lowercase())))
+                .count();
+            (hits, c)
+        })
+        .filter(|(hits, _)| *hits > 0)
+        .max_by_key(|(hits, _)| *hits)
+        .map(|(_, c)| c)
+        .unwrap_or(&banks.general)
+}
+
+// The topic as it should read mid-sentence: trimmed, shortened, and with a
+// typed capital dropped unless the first word is an acronym ("NASA") or a
+// proper-noun keyword ("Mars").
+fn display_topic(banks: &WordBanks, topic: &str) -> String {
+    let words: Vec<&str> = topic.split_whitespace().take(MAX_TOPIC_WORDS).collect();
+    let topic = words
+        .join(" ")
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_string();
+    let Some(first) = topic.split_whitespace().next() else {
+        return "new things".to_string();
+    };
+    let acronym = first.chars().filter(|c| c.is_alphabetic()).count() > 1
+        && first.chars().all(|c| !c.is_lowercase());
+    let first_key = word_key(&first.to_lowercase());
+    let proper_noun = banks
+        .categories
+        .iter()
+        .flat_map(|c| c.keywords.iter())
+        .any(|k| k.starts_with(char::is_uppercase) && word_key(&k.to_lowercase()) == first_key);
+    if acronym || proper_noun {
+        return topic;
+    }
+    let mut chars = topic.chars();
+    match chars.next() {
+        Some(c) => c.to_lowercase().chain(chars).collect(),
+        None => topic,
+    }
+}
+
+fn topic_words(topic: &str) -> impl Iterator<Item = String> + '_ {
+    topic
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+}
+
+// "a museum guide" -> "the museum guide"
+fn definite(phrase: &str) -> String {
+    let bare = phrase
+        .strip_prefix("a ")
+        .or_else(|| phrase.strip_prefix("an "))
+        .or_else(|| phrase.strip_prefix("the "))
+        .unwrap_or(phrase);
+    format!("the {}", bare)
+}
+
+// "apron" -> "an apron"
+fn indefinite(noun: &str) -> String {
+    let article = if noun.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        "an"
+    } else {
+        "a"
+    };
+    format!("{} {}", article, noun)
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// "dinosaurs and fossils" -> "Dinosaurs and Fossils"
+fn title_case(text: &str) -> String {
+    const SMALL: [&str; 8] = ["a", "an", "and", "the", "of", "in", "on", "at"];
+    text.split_whitespace()
+        .enumerate()
+        .map(|(i, w)| {
+            if i > 0 && SMALL.contains(&w) {
+                w.to_string()
+            } else {
+                capitalize(w)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_stories_validate_cleanly() {
+        for topic in ["dinosaurs", "a trip to Mars", "Baking cookies", "xyzzy", ""] {
+            for grade in [0, 3, 8] {
+                for paragraphs in 1..=6 {
+                    let story = generate_story(topic, grade, paragraphs, 6);
+                    let (_, report) = story.clone().validate().expect("story validates");
+                    assert!(report.is_clean(), "{:?}: {}", topic, report.summary());
+                    assert_eq!(story.paragraphs.len(), paragraphs as usize);
+                    assert!(!story.questions.is_empty());
+                    assert!(story.questions.len() <= 6);
+                    assert_eq!(story.grade_level, Some(grade));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn question_and_paragraph_counts_are_clamped() {
+        let story = generate_story("ocean", 4, 0, 0);
+        assert_eq!(story.paragraphs.len(), 1);
+        assert_eq!(story.questions.len(), 1);
+        let story = generate_story("ocean", 4, 9, 2);
+        assert_eq!(story.paragraphs.len(), 6);
+        assert_eq!(story.questions.len(), 2);
+    }
+
+    #[test]
+    fn questions_come_in_story_order_with_mixed_kinds() {
+        let story = generate_story("robots", 5, 6, 5);
+        let indexes: Vec<usize> = story.questions.iter().map(|q| q.paragraph_index).collect();
+        assert!(indexes.windows(2).all(|w| w[0] <= w[1]), "{:?}", indexes);
+        let mut kinds: Vec<&str> = story.questions.iter().map(|q| q.kind.label()).collect();
+        kinds.sort();
+        kinds.dedup();
+        assert!(kinds.len() > 1, "{:?}", kinds);
+    }
+
+    #[test]
+    fn topic_picks_the_category_with_the_most_keyword_hits() {
+        let banks = banks();
+        let space = banks
+            .categories
+            .iter()
+            .find(|c| c.keywords.iter().any(|k| k == "NASA"))
+            .unwrap();
+        assert!(std::ptr::eq(
+            match_category(banks, "Rockets to the Moon"),
+            space
+        ));
+        assert!(std::ptr::eq(
+            match_category(banks, "my grandma's quilt"),
+            &banks.general
+        ));
+    }
+
+    #[test]
+    fn display_topic_lowercases_unless_acronym_or_proper_noun() {
+        let banks = banks();
+        assert_eq!(display_topic(banks, "  Dinosaurs!  "), "dinosaurs");
+        assert_eq!(display_topic(banks, "NASA rockets"), "NASA rockets");
+        assert_eq!(display_topic(banks, "Mars rovers"), "Mars rovers");
+        assert_eq!(display_topic(banks, "   "), "new things");
+        assert_eq!(
+            display_topic(banks, "one two three four five six seven eight"),
+            "one two three four five six"
+        );
+    }
+
+    #[test]
+    fn articles_and_capitals() {
+        assert_eq!(definite("a museum guide"), "the museum guide");
+        assert_eq!(definite("an owl"), "the owl");
+        assert_eq!(definite("the reef"), "the reef");
+        assert_eq!(definite("park ranger"), "the park ranger");
+        assert_eq!(indefinite("apron"), "an apron");
+        assert_eq!(indefinite("map"), "a map");
+        assert_eq!(capitalize("éclair"), "Éclair");
+        assert_eq!(capitalize(""), "");
+        assert_eq!(
+            title_case("dinosaurs and fossils of the past"),
+            "Dinosaurs and Fossils of the Past"
+        );
+    }
+}
@@ -0,0 +1,567 @@
+// E:\rust_dev\kids_story_trainer\src\validate.rs
+//
+// Sanity checks for stories that did not come from our own source code
+// (the AI Worker today). Anything fixable is repaired in place; questions
+// that can't be fixed are dropped; a story with nothing left to read or
+// answer is rejected so the caller can use a fallback story instead.
+use std::fmt;
+
+use crate::{find_whole_word, split_sentences, Question, QuestionKind, Story};
+
+// Question numbers in issues are 1-based, as the kid would see them.
+#[derive(Clone, PartialEq, Debug)]
+pub enum StoryIssue {
+    MissingTitle,
+    BlankParagraphsRemoved { count: usize },
+    NoParagraphs,
+    NoUsableQuestions,
+    BlankQuestionText { question: usize },
+    ParagraphOutOfRange { question: usize, paragraph_index: usize },
+    DuplicateChoicesRemoved { question: usize, removed: usize },
+    TooFewChoices { question: usize },
+    BlankCorrectChoice { question: usize },
+    CorrectIndexOutOfRange { question: usize, correct_index: usize },
+    NoAcceptedAnswers { question: usize },
+    SentenceOutOfRange { question: usize, correct_sentence: usize },
+    InvalidSequence { question: usize },
+    VocabularyWordMissing { question: usize, word: String },
+}
+
+// What validation did about an issue. A warning is only reported; the
+// story is shown as it is.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IssueAction {
+    Repaired,
+    Warning,
+    QuestionDropped,
+    Unusable,
+}
+
+impl StoryIssue {
+    pub fn action(&self) -> IssueAction {
+        match self {
+            StoryIssue::MissingTitle
+            | StoryIssue::BlankParagraphsRemoved { .. }
+            | StoryIssue::DuplicateChoicesRemoved { .. } => IssueAction::Repaired,
+            StoryIssue::VocabularyWordMissing { .. } => IssueAction::Warning,
+            StoryIssue::NoParagraphs | StoryIssue::NoUsableQuestions => IssueAction::Unusable,
+            _ => IssueAction::QuestionDropped,
+        }
+    }
+}
+
+impl fmt::Display for StoryIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoryIssue::MissingTitle => write!(f, "story had no title"),
+            StoryIssue::BlankParagraphsRemoved { count } => {
+                write!(f, "{} blank paragraph(s) removed", count)
+            }
+            StoryIssue::NoParagraphs => write!(f, "story has no paragraphs"),
+            StoryIssue::NoUsableQuestions => write!(f, "story has no usable questions"),
+            StoryIssue::BlankQuestionText { question } => {
+                write!(f, "Q{} has no question text", question)
+            }
+            StoryIssue::ParagraphOutOfRange {
+                question,
+                paragraph_index,
+            } => write!(
+                f,
+                "Q{} points at paragraph {} which does not exist",
+                question,
+                paragraph_index + 1
+            ),
+            StoryIssue::DuplicateChoicesRemoved { question, removed } => {
+                write!(f, "Q{} had {} duplicate or blank choice(s)", question, removed)
+            }
+            StoryIssue::TooFewChoices { question } => {
+                write!(f, "Q{} has fewer than two choices", question)
+            }
+            StoryIssue::BlankCorrectChoice { question } => {
+                write!(f, "Q{} marks a blank choice as correct", question)
+            }
+            StoryIssue::CorrectIndexOutOfRange {
+                question,
+                correct_index,
+            } => write!(
+                f,
+                "Q{} marks choice {} as correct but it does not exist",
+                question,
+                correct_index + 1
+            ),
+            StoryIssue::NoAcceptedAnswers { question } => {
+                write!(f, "Q{} has no accepted answers", question)
+            }
+            StoryIssue::SentenceOutOfRange {
+                question,
+                correct_sentence,
+            } => write!(
+                f,
+                "Q{} points at sentence {} which does not exist",
+                question,
+                correct_sentence + 1
+            ),
+            StoryIssue::InvalidSequence { question } => {
+                write!(f, "Q{} has an invalid event order", question)
+            }
+            StoryIssue::VocabularyWordMissing { question, word } => write!(
+                f,
+                "Q{} asks about \"{}\" but the word is not in its paragraph",
+                question, word
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<StoryIssue>,
+}
+
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn dropped_questions(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| i.action() == IssueAction::QuestionDropped)
+            .count()
+    }
+
+    // One line for the error banner, e.g. "Q2 has fewer than two choices; …".
+    pub fn summary(&self) -> String {
+        const MAX_LISTED: usize = 3;
+        let mut parts: Vec<String> = self
+            .issues
+            .iter()
+            .take(MAX_LISTED)
+            .map(|i| i.to_string())
+            .collect();
+        if self.issues.len() > MAX_LISTED {
+            parts.push(format!("{} more", self.issues.len() - MAX_LISTED));
+        }
+        parts.join("; ")
+    }
+}
+
+impl Story {
+    // Repairs what it can and returns the cleaned story plus everything that
+    // was wrong, or just the report when the story can't be shown at all.
+    pub fn validate(mut self) -> Result<(Story, ValidationReport), ValidationReport> {
+        let mut report = ValidationReport::default();
+
+        self.title = self.title.trim().to_string();
+        if self.title.is_empty() {
+            self.title = "A Story for You".into();
+            report.issues.push(StoryIssue::MissingTitle);
+        }
+
+        // Drop blank paragraphs, remembering where the others moved so
+        // question paragraph indexes can follow them.
+        let mut new_index: Vec<Option<usize>> = Vec::with_capacity(self.paragraphs.len());
+        let mut paragraphs = Vec::with_capacity(self.paragraphs.len());
+        for p in self.paragraphs {
+            let p = p.trim().to_string();
+            if p.is_empty() {
+                new_index.push(None);
+            } else {
+                new_index.push(Some(paragraphs.len()));
+                paragraphs.push(p);
+            }
+        }
+        let blank = new_index.iter().filter(|i| i.is_none()).count();
+        if blank > 0 {
+            report.issues.push(StoryIssue::BlankParagraphsRemoved { count: blank });
+        }
+        self.paragraphs = paragraphs;
+        if self.paragraphs.is_empty() {
+            report.issues.push(StoryIssue::NoParagraphs);
+            return Err(report);
+        }
+
+        let questions = std::mem::take(&mut self.questions);
+        for (i, mut q) in questions.into_iter().enumerate() {
+            let number = i + 1;
+            q.text = q.text.trim().to_string();
+            if q.text.is_empty() {
+                report.issues.push(StoryIssue::BlankQuestionText { question: number });
+                continue;
+            }
+            match new_index.get(q.paragraph_index).copied().flatten() {
+                Some(idx) => q.paragraph_index = idx,
+                None => {
+                    report.issues.push(StoryIssue::ParagraphOutOfRange {
+                        question: number,
+                        paragraph_index: q.paragraph_index,
+                    });
+                    continue;
+                }
+            }
+            if repair_question(&mut q, number, &self.paragraphs, &mut report.issues) {
+                self.questions.push(q);
+            }
+        }
+
+        if self.questions.is_empty() {
+            report.issues.push(StoryIssue::NoUsableQuestions);
+            return Err(report);
+        }
+
+        Ok((self, report))
+    }
+}
+
+// Returns false if the question must be dropped. `paragraph_index` is
+// already known to be valid.
+fn repair_question(
+    q: &mut Question,
+    number: usize,
+    paragraphs: &[String],
+    issues: &mut Vec<StoryIssue>,
+) -> bool {
+    let paragraph = &paragraphs[q.paragraph_index];
+    match &mut q.kind {
+        QuestionKind::MultipleChoice {
+            choices,
+            correct_index,
+        } => repair_choices(choices, correct_index, number, issues),
+        QuestionKind::Vocabulary {
+            word,
+            choices,
+            correct_index,
+        } => {
+            *word = word.trim().to_string();
+            if find_whole_word(paragraph, word).is_empty() {
+                // Still a fair question; the word just won't be highlighted.
+                issues.push(StoryIssue::VocabularyWordMissing {
+                    question: number,
+                    word: word.clone(),
+                });
+            }
+            repair_choices(choices, correct_index, number, issues)
+        }
+        QuestionKind::ShortAnswer {
+            accepted_answers, ..
+        } => {
+            accepted_answers.retain(|a| !a.trim().is_empty());
+            if accepted_answers.is_empty() {
+                issues.push(StoryIssue::NoAcceptedAnswers { question: number });
+                return false;
+            }
+            true
+        }
+        QuestionKind::TrueFalse { .. } => true,
+        QuestionKind::EvidenceSelect { correct_sentence } => {
+            if *correct_sentence >= split_sentences(paragraph).len() {
+                issues.push(StoryIssue::SentenceOutOfRange {
+                    question: number,
+                    correct_sentence: *correct_sentence,
+                });
+                return false;
+            }
+            true
+        }
+        QuestionKind::Sequence {
+            events,
+            correct_order,
+        } => {
+            let mut sorted = correct_order.clone();
+            sorted.sort_unstable();
+            let is_permutation = sorted.iter().copied().eq(0..events.len());
+            if events.len() < 2 || !is_permutation {
+                issues.push(StoryIssue::InvalidSequence { question: number });
+                return false;
+            }
+            true
+        }
+    }
+}
+
+// Trims and de-duplicates choices (case-insensitively), keeping
+// `correct_index` pointing at the same answer text.
+fn repair_choices(
+    choices: &mut Vec<String>,
+    correct_index: &mut usize,
+    number: usize,
+    issues: &mut Vec<StoryIssue>,
+) -> bool {
+    if *correct_index >= choices.len() {
+        issues.push(StoryIssue::CorrectIndexOutOfRange {
+            question: number,
+            correct_index: *correct_index,
+        });
+        return false;
+    }
+
+    let correct_key = choices[*correct_index].trim().to_lowercase();
+    if correct_key.is_empty() {
+        issues.push(StoryIssue::BlankCorrectChoice { question: number });
+        return false;
+    }
+    let mut kept: Vec<String> = Vec::with_capacity(choices.len());
+    let mut removed = 0;
+    for choice in choices.drain(..) {
+        let choice = choice.trim().to_string();
+        let key = choice.to_lowercase();
+        if choice.is_empty() || kept.iter().any(|k| k.to_lowercase() == key) {
+            removed += 1;
+        } else {
+            kept.push(choice);
+        }
+    }
+    if removed > 0 {
+        issues.push(StoryIssue::DuplicateChoicesRemoved {
+            question: number,
+            removed,
+        });
+    }
+
+    *correct_index = kept
+        .iter()
+        .position(|c| c.to_lowercase() == correct_key)
+        .unwrap_or(usize::MAX);
+    *choices = kept;
+
+    if choices.len() < 2 || *correct_index >= choices.len() {
+        issues.push(StoryIssue::TooFewChoices { question: number });
+        return false;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(paragraph_index: usize, kind: QuestionKind) -> Question {
+        Question {
+            text: "What happened?".into(),
+            paragraph_index,
+            kind,
+        }
+    }
+
+    fn multiple_choice(choices: &[&str], correct_index: usize) -> QuestionKind {
+        QuestionKind::MultipleChoice {
+            choices: choices.iter().map(|c| c.to_string()).collect(),
+            correct_index,
+        }
+    }
+
+    fn story(paragraphs: &[&str], questions: Vec<Question>) -> Story {
+        Story {
+            title: "The Fox".into(),
+            paragraphs: paragraphs.iter().map(|p| p.to_string()).collect(),
+            questions,
+            grade_level: None,
+        }
+    }
+
+    #[test]
+    fn clean_story_passes_unchanged() {
+        let st = story(
+            &["A fox ran. It was fast."],
+            vec![question(0, multiple_choice(&["A fox", "A cat"], 0))],
+        );
+        let (fixed, report) = st.clone().validate().unwrap();
+        assert!(report.is_clean());
+        assert_eq!(fixed, st);
+    }
+
+    #[test]
+    fn blank_paragraphs_are_removed_and_indexes_follow() {
+        let st = story(
+            &["", "A fox ran.", "  ", "It slept."],
+            vec![
+                question(3, QuestionKind::TrueFalse { answer: true }),
+                question(2, QuestionKind::TrueFalse { answer: false }),
+            ],
+        );
+        let (fixed, report) = st.validate().unwrap();
+        assert_eq!(fixed.paragraphs, vec!["A fox ran.", "It slept."]);
+        assert_eq!(fixed.questions.len(), 1);
+        assert_eq!(fixed.questions[0].paragraph_index, 1);
+        assert_eq!(
+            report.issues,
+            vec![
+                StoryIssue::BlankParagraphsRemoved { count: 2 },
+                StoryIssue::ParagraphOutOfRange {
+                    question: 2,
+                    paragraph_index: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_title_is_repaired() {
+        let mut st = story(
+            &["A fox ran."],
+            vec![question(0, QuestionKind::TrueFalse { answer: true })],
+        );
+        st.title = " ".into();
+        let (fixed, report) = st.validate().unwrap();
+        assert_eq!(fixed.title, "A Story for You");
+        assert_eq!(report.issues[0].action(), IssueAction::Repaired);
+    }
+
+    #[test]
+    fn duplicate_choices_are_removed_keeping_the_answer() {
+        let st = story(
+            &["A fox ran."],
+            vec![question(0, multiple_choice(&["Cat", "cat ", "", "Fox"], 3))],
+        );
+        let (fixed, report) = st.validate().unwrap();
+        assert_eq!(fixed.questions[0].kind, multiple_choice(&["Cat", "Fox"], 1));
+        assert_eq!(
+            report.issues,
+            vec![StoryIssue::DuplicateChoicesRemoved {
+                question: 1,
+                removed: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn blank_correct_choice_drops_the_question() {
+        let st = story(
+            &["A fox ran."],
+            vec![
+                question(0, multiple_choice(&["Cat", " ", "Fox"], 1)),
+                question(0, QuestionKind::TrueFalse { answer: true }),
+            ],
+        );
+        let (fixed, report) = st.validate().unwrap();
+        assert_eq!(fixed.questions.len(), 1);
+        assert_eq!(
+            report.issues,
+            vec![StoryIssue::BlankCorrectChoice { question: 1 }]
+        );
+        assert_eq!(report.dropped_questions(), 1);
+    }
+
+    #[test]
+    fn too_few_choices_and_bad_index_drop_the_question() {
+        let st = story(
+            &["A fox ran."],
+            vec![
+                question(0, multiple_choice(&["Fox", "fox"], 0)),
+                question(0, multiple_choice(&["Fox", "Cat"], 2)),
+            ],
+        );
+        let report = st.validate().unwrap_err();
+        assert!(report
+            .issues
+            .contains(&StoryIssue::TooFewChoices { question: 1 }));
+        assert!(report.issues.contains(&StoryIssue::CorrectIndexOutOfRange {
+            question: 2,
+            correct_index: 2
+        }));
+        assert_eq!(report.issues.last(), Some(&StoryIssue::NoUsableQuestions));
+    }
+
+    #[test]
+    fn missing_vocabulary_word_is_only_a_warning() {
+        let st = story(
+            &["A fox ran."],
+            vec![question(
+                0,
+                QuestionKind::Vocabulary {
+                    word: "swift".into(),
+                    choices: vec!["fast".into(), "slow".into()],
+                    correct_index: 0,
+                },
+            )],
+        );
+        let (fixed, report) = st.validate().unwrap();
+        assert_eq!(fixed.questions.len(), 1);
+        assert_eq!(report.issues[0].action(), IssueAction::Warning);
+    }
+
+    #[test]
+    fn vocabulary_word_must_appear_as_a_whole_word() {
+        let vocabulary = |word: &str| {
+            question(
+                0,
+                QuestionKind::Vocabulary {
+                    word: word.into(),
+                    choices: vec!["a bug".into(), "a tree".into()],
+                    correct_index: 0,
+                },
+            )
+        };
+        let (_, report) = story(&["A giant ran."], vec![vocabulary("ant")])
+            .validate()
+            .unwrap();
+        assert_eq!(
+            report.issues,
+            vec![StoryIssue::VocabularyWordMissing {
+                question: 1,
+                word: "ant".into()
+            }]
+        );
+        let (_, report) = story(&["An Ant ran."], vec![vocabulary(" ant ")])
+            .validate()
+            .unwrap();
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn evidence_and_sequence_are_checked() {
+        let st = story(
+            &["A fox ran. It slept."],
+            vec![
+                question(
+                    0,
+                    QuestionKind::EvidenceSelect {
+                        correct_sentence: 1,
+                    },
+                ),
+                question(
+                    0,
+                    QuestionKind::EvidenceSelect {
+                        correct_sentence: 2,
+                    },
+                ),
+                question(
+                    0,
+                    QuestionKind::Sequence {
+                        events: vec!["slept".into(), "ran".into()],
+                        correct_order: vec![1, 0],
+                    },
+                ),
+                question(
+                    0,
+                    QuestionKind::Sequence {
+                        events: vec!["slept".into(), "ran".into()],
+                        correct_order: vec![1, 1],
+                    },
+                ),
+            ],
+        );
+        let (fixed, report) = st.validate().unwrap();
+        assert_eq!(fixed.questions.len(), 2);
+        assert_eq!(
+            report.issues,
+            vec![
+                StoryIssue::SentenceOutOfRange {
+                    question: 2,
+                    correct_sentence: 2
+                },
+                StoryIssue::InvalidSequence { question: 4 },
+            ]
+        );
+    }
+
+    #[test]
+    fn story_without_paragraphs_is_unusable() {
+        let report = story(&[" "], Vec::new()).validate().unwrap_err();
+        assert_eq!(report.issues.last(), Some(&StoryIssue::NoParagraphs));
+        assert_eq!(
+            report.issues.last().unwrap().action(),
+            IssueAction::Unusable
+        );
+    }
+}